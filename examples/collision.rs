@@ -0,0 +1,258 @@
+//! This example demonstrates projectiles bouncing off colliders and spawning sparks on impact.
+mod util;
+use berdicles::{
+    util::random_circle, CollisionResponse, ErasedEventParticleSystem, EventProjectileSystem,
+    ExpirationState, InstancedMaterial3d, ParticleSeed, PhysicsProjectile, Projectile,
+    ProjectileCluster, ProjectileCollider, ProjectileCollision, ProjectileEvent,
+    ProjectileEventBuffer, ProjectileEventType, ProjectileParent, ProjectilePlugin,
+    ProjectileSystem, StandardParticle,
+};
+use bevy::{prelude::*, window::PresentMode};
+use util::{uv_debug_texture, FPSPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        present_mode: PresentMode::AutoNoVsync,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+        )
+        .add_plugins(FPSPlugin)
+        .add_plugins(ProjectilePlugin::default())
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ball {
+    pub seed: ParticleSeed,
+    pub life_time: f32,
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+impl PhysicsProjectile for Ball {
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn set_position(&mut self, position: Vec3) {
+        self.position = position
+    }
+
+    fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    fn set_velocity(&mut self, velocity: Vec3) {
+        self.velocity = velocity
+    }
+
+    fn expire(&mut self, _: ExpirationState) {
+        self.life_time = f32::INFINITY
+    }
+}
+
+impl Projectile for Ball {
+    fn get_seed(&self) -> ParticleSeed {
+        self.seed
+    }
+
+    fn get_lifetime(&self) -> f32 {
+        self.life_time
+    }
+
+    fn get_transform(&self) -> Transform {
+        Transform::from_translation(self.position)
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.life_time += dt;
+        self.velocity.y -= 9.8 * dt;
+        self.position += self.velocity * dt;
+    }
+
+    fn expiration_state(&self) -> ExpirationState {
+        ExpirationState::fizzle_if(self.life_time > 8.)
+    }
+
+    fn as_physics_mut(&mut self) -> Option<&mut dyn PhysicsProjectile> {
+        Some(self)
+    }
+}
+
+pub struct BallSpawner(f32);
+
+impl ProjectileSystem for BallSpawner {
+    type Projectile = Ball;
+
+    fn capacity(&self) -> usize {
+        100
+    }
+
+    fn spawn_step(&mut self, time: f32) -> usize {
+        self.0 += time * 8.;
+        let result = self.0.floor() as usize;
+        self.0 = self.0.fract();
+        result
+    }
+
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
+        let xz = random_circle(seed) * 4.;
+        Ball {
+            seed,
+            life_time: 0.,
+            position: Vec3::new(0., 12., 0.),
+            velocity: Vec3::new(xz.x, 0., xz.y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Spark {
+    pub seed: ParticleSeed,
+    pub life_time: f32,
+    pub origin: Vec3,
+    pub normal: Vec3,
+}
+
+impl Projectile for Spark {
+    fn get_seed(&self) -> ParticleSeed {
+        self.seed
+    }
+
+    fn get_lifetime(&self) -> f32 {
+        self.life_time
+    }
+
+    fn get_transform(&self) -> Transform {
+        let p = random_circle(self.seed);
+        let dir = (self.normal + Vec3::new(p.x, 0., p.y)).normalize_or_zero();
+        Transform::from_translation(self.origin + dir * self.life_time * 2.)
+            .with_scale(Vec3::splat(1. - self.life_time * 2.))
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.life_time += dt;
+    }
+
+    fn expiration_state(&self) -> ExpirationState {
+        ExpirationState::fizzle_if(self.life_time > 0.5)
+    }
+}
+
+pub struct SparkSpawner;
+
+impl ProjectileSystem for SparkSpawner {
+    type Projectile = Spark;
+
+    fn capacity(&self) -> usize {
+        10000
+    }
+
+    fn spawn_step(&mut self, _: f32) -> usize {
+        0
+    }
+
+    fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
+        unreachable!()
+    }
+
+    fn as_event_particle_system(&mut self) -> Option<&mut dyn ErasedEventParticleSystem> {
+        Some(self)
+    }
+}
+
+impl EventProjectileSystem for SparkSpawner {
+    fn spawn_on_event(&mut self, parent: &ProjectileEvent) -> usize {
+        match parent.event {
+            ProjectileEventType::Collide => 4,
+            _ => 0,
+        }
+    }
+
+    fn build_sub_projectile(parent: &ProjectileEvent, seed: ParticleSeed) -> Self::Projectile {
+        Spark {
+            seed,
+            life_time: 0.,
+            origin: parent.position,
+            normal: parent.normal,
+        }
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut materials2: ResMut<Assets<StandardParticle>>,
+) {
+    let root = commands
+        .spawn((
+            ProjectileCluster::new(BallSpawner(0.)),
+            Mesh3d(meshes.add(Sphere::new(0.2).mesh())),
+            InstancedMaterial3d(materials2.add(StandardParticle {
+                base_color: LinearRgba::new(2., 2., 2., 1.),
+                texture: images.add(uv_debug_texture()),
+                alpha_mode: AlphaMode::Opaque,
+                ..Default::default()
+            })),
+            ProjectileCollision::new(CollisionResponse::Bounce {
+                restitution: 0.7,
+                friction: 0.1,
+            })
+            .with_radius(0.2),
+            ProjectileEventBuffer::default(),
+        ))
+        .id();
+
+    commands.spawn((
+        ProjectileCluster::new(SparkSpawner),
+        Mesh3d(meshes.add(Cuboid::new(0.1, 0.1, 0.1).mesh())),
+        InstancedMaterial3d(materials2.add(StandardParticle {
+            base_color: LinearRgba::new(4., 2., 0., 1.),
+            texture: images.add(uv_debug_texture()),
+            alpha_mode: AlphaMode::Opaque,
+            ..Default::default()
+        })),
+        ProjectileParent(root),
+    ));
+
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(2.).mesh())),
+        MeshMaterial3d(materials.add(StandardMaterial::from_color(Srgba::BLUE))),
+        Transform::from_xyz(0., 4., 0.),
+        ProjectileCollider::Sphere { radius: 2. },
+    ));
+
+    commands.spawn((
+        PointLight {
+            shadows_enabled: true,
+            intensity: 10_000_000.,
+            range: 100.0,
+            shadow_depth_bias: 0.2,
+            ..default()
+        },
+        Transform::from_xyz(8.0, 16.0, 8.0),
+    ));
+
+    // ground plane
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0).subdivisions(10))),
+        MeshMaterial3d(materials.add(StandardMaterial::from_color(Srgba::GREEN))),
+        Transform::from_xyz(0., 0., 0.),
+        ProjectileCollider::Plane,
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 7., 30.0).looking_at(Vec3::new(0., 0., 0.), Vec3::Y),
+    ));
+}
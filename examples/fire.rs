@@ -6,8 +6,8 @@ use std::f32::consts::PI;
 use berdicles::{
     util::{into_rng, map_range, random_cone, random_sphere, spawn_rate},
     DefaultInstanceBuffer, ExpirationState, ExtendedInstancedMaterial, InstancedMaterial3d,
-    InstancedMaterialExtension, InstancedMaterialPlugin, Projectile, ProjectileCluster,
-    ProjectilePlugin, ProjectileSystem, StandardParticle,
+    InstancedMaterialExtension, InstancedMaterialPlugin, ParticleSeed, Projectile,
+    ProjectileCluster, ProjectilePlugin, ProjectileSystem, StandardParticle,
 };
 use bevy::{
     core_pipeline::bloom::Bloom,
@@ -31,7 +31,7 @@ fn main() {
                 }),
         )
         .add_plugins(FPSPlugin)
-        .add_plugins(ProjectilePlugin)
+        .add_plugins(ProjectilePlugin::default())
         .add_systems(Startup, setup)
         .add_plugins(InstancedMaterialPlugin::<
             ExtendedInstancedMaterial<StandardParticle, ErosionExt>,
@@ -146,13 +146,17 @@ impl ProjectileSystem for MySpawner {
         spawn_rate(&mut self.spawn_meta, self.spawn_rate, time)
     }
 
-    fn build_particle(&self, seed: f32) -> Self::Projectile {
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
         let mut rng = into_rng(seed);
         MyParticle {
-            position: self.position + random_sphere(rng.f32()) * 0.1,
-            //velocity: random_sphere(rng.f32()) * 0.4,
-            velocity: random_cone(Vec3::Y, 40.0f32.to_radians(), rng.f32())
-                * (rng.f32() * (self.speed_range.1 - self.speed_range.0) + self.speed_range.0),
+            position: self.position + random_sphere(ParticleSeed::from_rng(&mut rng)) * 0.1,
+            //velocity: random_sphere(ParticleSeed::from_rng(&mut rng)) * 0.4,
+            velocity: random_cone(
+                Vec3::Y,
+                40.0f32.to_radians(),
+                ParticleSeed::from_rng(&mut rng),
+            ) * (rng.f32() * (self.speed_range.1 - self.speed_range.0)
+                + self.speed_range.0),
             rotation: rng.f32() * 2. * PI,
             angular_velocity: (rng.f32() / 4. + 0.25) * if rng.bool() { 1.0 } else { -1.0 },
             life_time: 0.,
@@ -11,7 +11,7 @@ use std::f32::consts::PI;
 use berdicles::{
     util::into_rng, DefaultInstanceBuffer, ExpirationState, ExtendedInstancedMaterial,
     HairParticles, InstancedMaterial3d, InstancedMaterialExtension, InstancedMaterialPlugin,
-    Projectile, ProjectilePlugin, ProjectileSystem, StandardParticle,
+    ParticleSeed, Projectile, ProjectilePlugin, ProjectileSystem, StandardParticle,
 };
 use bevy::{
     prelude::*,
@@ -92,7 +92,7 @@ fn main() {
                 .insert(&GRASS_SHADER, Shader::from_wgsl(GRASS_VERTEX, "grass.wgsl"))
         })
         .add_plugins(FPSPlugin)
-        .add_plugins(ProjectilePlugin)
+        .add_plugins(ProjectilePlugin::default())
         .add_systems(Startup, setup)
         .add_systems(Update, update)
         .insert_resource(Noises {
@@ -104,11 +104,11 @@ fn main() {
 
 #[derive(Debug, Clone, Copy)]
 pub struct MyParticle {
-    pub seed: f32,
+    pub seed: ParticleSeed,
 }
 
 impl Projectile for MyParticle {
-    fn get_seed(&self) -> f32 {
+    fn get_seed(&self) -> ParticleSeed {
         self.seed
     }
 
@@ -147,7 +147,7 @@ impl ProjectileSystem for MySpawner {
         80000
     }
 
-    fn build_particle(&self, seed: f32) -> Self::Projectile {
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
         MyParticle { seed }
     }
 }
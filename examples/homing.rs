@@ -0,0 +1,179 @@
+//! This example demonstrates homing missiles chasing a moving target.
+mod util;
+use berdicles::{
+    templates::{Homing, HomingState, HomingTarget},
+    util::random_cone,
+    ExpirationState, InstancedMaterial3d, ParticleSeed, Projectile, ProjectileCluster,
+    ProjectilePlugin, ProjectileSystem, StandardParticle,
+};
+use bevy::{prelude::*, window::PresentMode};
+use std::f32::consts::PI;
+use util::{uv_debug_texture, FPSPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        present_mode: PresentMode::AutoNoVsync,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+        )
+        .add_plugins(FPSPlugin)
+        .add_plugins(ProjectilePlugin::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_target)
+        .run();
+}
+
+#[derive(Component)]
+struct Target;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Missile {
+    pub seed: ParticleSeed,
+    pub homing: Homing,
+}
+
+impl Projectile for Missile {
+    fn get_seed(&self) -> ParticleSeed {
+        self.seed
+    }
+
+    fn get_lifetime(&self) -> f32 {
+        self.homing.age
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.homing.transform()
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.homing.update(dt);
+    }
+
+    fn expiration_state(&self) -> ExpirationState {
+        match self.homing.state {
+            HomingState::Reached => ExpirationState::Explode,
+            _ => ExpirationState::fizzle_if(self.homing.age > 10.),
+        }
+    }
+
+    fn as_homing_mut(&mut self) -> Option<&mut Homing> {
+        Some(&mut self.homing)
+    }
+}
+
+pub struct MissileLauncher {
+    pub target: Entity,
+    pub cooldown: f32,
+}
+
+impl ProjectileSystem for MissileLauncher {
+    type Projectile = Missile;
+
+    const WORLD_SPACE: bool = true;
+
+    fn capacity(&self) -> usize {
+        100
+    }
+
+    fn spawn_step(&mut self, time: f32) -> usize {
+        self.cooldown += time * 4.;
+        let result = self.cooldown.floor() as usize;
+        self.cooldown = self.cooldown.fract();
+        result
+    }
+
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
+        Missile {
+            seed,
+            homing: Homing {
+                turn_rate: 3.,
+                speed: |t| 4. + t * 4.,
+                ..Homing::new(
+                    self.target,
+                    Vec3::ZERO,
+                    random_cone(Vec3::Y, PI / 4., seed) * 4.,
+                )
+            },
+        }
+    }
+}
+
+fn move_target(time: Res<Time>, mut query: Query<&mut Transform, With<Target>>) {
+    let t = time.elapsed_secs();
+    for mut transform in &mut query {
+        transform.translation = Vec3::new(t.cos() * 10., 6., t.sin() * 10.);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut materials2: ResMut<Assets<StandardParticle>>,
+) {
+    let target = commands
+        .spawn((
+            Target,
+            HomingTarget::default(),
+            Mesh3d(meshes.add(Sphere::new(0.5).mesh())),
+            MeshMaterial3d(materials.add(StandardMaterial::from_color(Srgba::RED))),
+            Transform::from_xyz(10., 6., 0.),
+        ))
+        .id();
+
+    commands.spawn((
+        ProjectileCluster::new(MissileLauncher {
+            target,
+            cooldown: 0.,
+        }),
+        Mesh3d(
+            meshes.add(
+                Mesh::from(
+                    Cone {
+                        radius: 0.2,
+                        height: 0.6,
+                    }
+                    .mesh(),
+                )
+                .rotated_by(Quat::from_rotation_x(-PI / 2.0)),
+            ),
+        ),
+        InstancedMaterial3d(materials2.add(StandardParticle {
+            base_color: LinearRgba::new(2., 2., 2., 1.),
+            texture: images.add(uv_debug_texture()),
+            alpha_mode: AlphaMode::Opaque,
+            ..Default::default()
+        })),
+    ));
+
+    commands.spawn((
+        PointLight {
+            shadows_enabled: true,
+            intensity: 10_000_000.,
+            range: 100.0,
+            shadow_depth_bias: 0.2,
+            ..default()
+        },
+        Transform::from_xyz(8.0, 16.0, 8.0),
+    ));
+
+    // ground plane
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0).subdivisions(10))),
+        MeshMaterial3d(materials.add(StandardMaterial::from_color(Srgba::GREEN))),
+        Transform::from_xyz(0., 0., 0.),
+    ));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 7., 30.0).looking_at(Vec3::new(0., 0., 0.), Vec3::Y),
+    ));
+}
@@ -5,8 +5,8 @@ mod util;
 use berdicles::{
     util::{random_cone, transform_from_derivative},
     DefaultInstanceBuffer, ExpirationState, ExtendedInstancedMaterial, InstancedMaterial3d,
-    InstancedMaterialExtension, InstancedMaterialPlugin, Projectile, ProjectileCluster,
-    ProjectilePlugin, ProjectileRef, ProjectileSystem, StandardParticle,
+    InstancedMaterialExtension, InstancedMaterialPlugin, ParticleSeed, Projectile,
+    ProjectileCluster, ProjectilePlugin, ProjectileRef, ProjectileSystem, StandardParticle,
 };
 use bevy::{
     prelude::*,
@@ -38,19 +38,19 @@ fn main() {
                 .insert(&SPIN_SHADER, Shader::from_wgsl(SPIN_VERTEX, "spin.wgsl"))
         })
         .add_plugins(FPSPlugin)
-        .add_plugins(ProjectilePlugin)
+        .add_plugins(ProjectilePlugin::default())
         .add_systems(Startup, setup)
         .run();
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct MyParticle {
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub life_time: f32,
 }
 
 impl Projectile for MyParticle {
-    fn get_seed(&self) -> f32 {
+    fn get_seed(&self) -> ParticleSeed {
         self.seed
     }
 
@@ -96,7 +96,7 @@ impl ProjectileSystem for MySpawner {
         result
     }
 
-    fn build_particle(&self, seed: f32) -> Self::Projectile {
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
         MyParticle {
             seed,
             life_time: 0.,
@@ -4,8 +4,8 @@ use berdicles::{
     templates::{ExpDecayTrail, WidthCurve},
     trail::{TrailMaterial, TrailMeshOf},
     util::transform_from_derivative,
-    ExpirationState, InstancedMaterial3d, Projectile, ProjectileCluster, ProjectilePlugin,
-    ProjectileSystem, StandardParticle,
+    ExpirationState, InstancedMaterial3d, ParticleSeed, Projectile, ProjectileCluster,
+    ProjectilePlugin, ProjectileSystem, StandardParticle,
 };
 use bevy::{
     pbr::{NotShadowCaster, NotShadowReceiver},
@@ -30,7 +30,7 @@ fn main() {
                 }),
         )
         .add_plugins(FPSPlugin)
-        .add_plugins(ProjectilePlugin)
+        .add_plugins(ProjectilePlugin::default())
         .add_systems(Startup, setup)
         .run();
 }
@@ -41,7 +41,7 @@ struct Shape;
 
 #[derive(Debug, Clone, Copy)]
 pub struct MainParticle {
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub life_time: f32,
     pub trail: ExpDecayTrail<16>,
 }
@@ -49,7 +49,7 @@ pub struct MainParticle {
 impl Projectile for MainParticle {
     fn get_transform(&self) -> Transform {
         let f = |t: f32| {
-            let front = Vec2::from_angle(self.seed * PI * 4.);
+            let front = Vec2::from_angle(self.seed.as_f32() * PI * 4.);
             let front = Vec3::new(front.x, 0., front.y);
             let tangent = front.cross(Vec3::Y);
             let angle = t * 12.;
@@ -104,7 +104,7 @@ impl ProjectileSystem for MainSpawner {
         result
     }
 
-    fn build_particle(&self, seed: f32) -> Self::Projectile {
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
         MainParticle {
             seed,
             life_time: 0.,
@@ -4,8 +4,8 @@ mod util;
 
 use berdicles::{
     util::{random_sphere, transform_from_derivative},
-    ExpirationState, InstancedMaterial3d, Projectile, ProjectileCluster, ProjectilePlugin,
-    ProjectileSystem, StandardParticle,
+    ExpirationState, InstancedMaterial3d, ParticleSeed, Projectile, ProjectileCluster,
+    ProjectilePlugin, ProjectileSystem, StandardParticle,
 };
 use bevy::{prelude::*, window::PresentMode};
 use std::f32::consts::PI;
@@ -25,19 +25,19 @@ fn main() {
                 }),
         )
         .add_plugins(FPSPlugin)
-        .add_plugins(ProjectilePlugin)
+        .add_plugins(ProjectilePlugin::default())
         .add_systems(Startup, setup)
         .run();
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct MyParticle {
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub life_time: f32,
 }
 
 impl Projectile for MyParticle {
-    fn get_seed(&self) -> f32 {
+    fn get_seed(&self) -> ParticleSeed {
         self.seed
     }
 
@@ -83,7 +83,7 @@ impl ProjectileSystem for MySpawner {
         result
     }
 
-    fn build_particle(&self, seed: f32) -> Self::Projectile {
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
         MyParticle {
             seed,
             life_time: 0.,
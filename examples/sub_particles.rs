@@ -3,9 +3,9 @@ mod util;
 use berdicles::{
     util::{random_circle, transform_from_derivative},
     ErasedEventParticleSystem, ErasedSubParticleSystem, EventProjectileSystem, ExpirationState,
-    InstancedMaterial3d, ProjectileEventType, Projectile, ProjectileCluster, ProjectileEvent,
-    ProjectileEventBuffer, ProjectileParent, ProjectilePlugin, ProjectileSystem, StandardParticle,
-    SubProjectileSystem,
+    InstancedMaterial3d, ParticleSeed, Projectile, ProjectileCluster, ProjectileEvent,
+    ProjectileEventBuffer, ProjectileEventType, ProjectileParent, ProjectilePlugin,
+    ProjectileSystem, StandardParticle, SubProjectileSystem,
 };
 use bevy::{prelude::*, window::PresentMode};
 use std::f32::consts::PI;
@@ -25,7 +25,7 @@ fn main() {
                 }),
         )
         .add_plugins(FPSPlugin)
-        .add_plugins(ProjectilePlugin)
+        .add_plugins(ProjectilePlugin::default())
         .add_systems(Startup, setup)
         .run();
 }
@@ -36,13 +36,13 @@ struct Shape;
 
 #[derive(Debug, Clone, Copy)]
 pub struct MainParticle {
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub life_time: f32,
     pub meta: f32,
 }
 
 impl Projectile for MainParticle {
-    fn get_seed(&self) -> f32 {
+    fn get_seed(&self) -> ParticleSeed {
         self.seed
     }
 
@@ -53,7 +53,7 @@ impl Projectile for MainParticle {
     fn get_transform(&self) -> Transform {
         let f = |t| {
             let z = t * 8. - t * t;
-            let xy: Vec2 = Vec2::from_angle(self.seed * PI * 4.) * t;
+            let xy: Vec2 = Vec2::from_angle(self.seed.as_f32() * PI * 4.) * t;
             Vec3::new(xy.x, z, xy.y)
         };
         transform_from_derivative(f, self.life_time)
@@ -92,7 +92,7 @@ impl ProjectileSystem for MainSpawner {
         result
     }
 
-    fn build_particle(&self, seed: f32) -> Self::Projectile {
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
         MainParticle {
             seed,
             life_time: 0.,
@@ -104,12 +104,12 @@ impl ProjectileSystem for MainSpawner {
 #[derive(Debug, Clone, Copy)]
 pub struct TrailParticle {
     pub origin: Transform,
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub life_time: f32,
 }
 
 impl Projectile for TrailParticle {
-    fn get_seed(&self) -> f32 {
+    fn get_seed(&self) -> ParticleSeed {
         self.seed
     }
 
@@ -154,7 +154,7 @@ impl ProjectileSystem for ChildSpawner {
         0
     }
 
-    fn build_particle(&self, _: f32) -> Self::Projectile {
+    fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
         unreachable!()
     }
 
@@ -173,7 +173,7 @@ impl SubProjectileSystem for ChildSpawner {
         result
     }
 
-    fn build_sub_projectile(parent: &Self::Parent, seed: f32) -> Self::Projectile {
+    fn build_sub_projectile(parent: &Self::Parent, seed: ParticleSeed) -> Self::Projectile {
         TrailParticle {
             origin: parent
                 .get_transform()
@@ -187,12 +187,12 @@ impl SubProjectileSystem for ChildSpawner {
 #[derive(Debug, Clone, Copy)]
 pub struct CollisionParticle {
     pub origin: Vec3,
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub life_time: f32,
 }
 
 impl Projectile for CollisionParticle {
-    fn get_seed(&self) -> f32 {
+    fn get_seed(&self) -> ParticleSeed {
         self.seed
     }
 
@@ -234,7 +234,7 @@ impl ProjectileSystem for CollisionSpawner {
         0
     }
 
-    fn build_particle(&self, _: f32) -> Self::Projectile {
+    fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
         unreachable!()
     }
 
@@ -251,7 +251,7 @@ impl EventProjectileSystem for CollisionSpawner {
         }
     }
 
-    fn build_sub_projectile(parent: &ProjectileEvent, seed: f32) -> Self::Projectile {
+    fn build_sub_projectile(parent: &ProjectileEvent, seed: ParticleSeed) -> Self::Projectile {
         CollisionParticle {
             origin: parent.position,
             seed,
@@ -4,8 +4,8 @@ use berdicles::{
     templates::{ExpDecayTrail, WidthCurve},
     trail::{TrailMaterial, TrailMeshOf},
     util::transform_from_derivative,
-    ExpirationState, InstancedMaterial3d, Projectile, ProjectileCluster, ProjectilePlugin,
-    ProjectileSystem, StandardParticle,
+    ExpirationState, InstancedMaterial3d, ParticleSeed, Projectile, ProjectileCluster,
+    ProjectilePlugin, ProjectileSystem, StandardParticle,
 };
 use bevy::{
     pbr::{NotShadowCaster, NotShadowReceiver},
@@ -30,7 +30,7 @@ fn main() {
                 }),
         )
         .add_plugins(FPSPlugin)
-        .add_plugins(ProjectilePlugin)
+        .add_plugins(ProjectilePlugin::default())
         .add_systems(Startup, setup)
         .add_systems(Update, spin)
         .run();
@@ -42,14 +42,14 @@ struct Shape;
 
 #[derive(Debug, Clone, Copy)]
 pub struct MainParticle {
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub life_time: f32,
     pub meta: f32,
     pub trail: ExpDecayTrail<16>,
 }
 
 impl Projectile for MainParticle {
-    fn get_seed(&self) -> f32 {
+    fn get_seed(&self) -> ParticleSeed {
         self.seed
     }
 
@@ -60,7 +60,7 @@ impl Projectile for MainParticle {
     fn get_transform(&self) -> Transform {
         let f = |t| {
             let z = t * 8. - t * t;
-            let xy: Vec2 = Vec2::from_angle(self.seed * PI * 4.) * t;
+            let xy: Vec2 = Vec2::from_angle(self.seed.as_f32() * PI * 4.) * t;
             Vec3::new(xy.x, z, xy.y)
         };
         transform_from_derivative(f, self.life_time)
@@ -69,7 +69,7 @@ impl Projectile for MainParticle {
     fn get_position(&self) -> Vec3 {
         let t = self.life_time;
         let z = t * 8. - t * t;
-        let xy: Vec2 = Vec2::from_angle(self.seed * PI * 4.) * t;
+        let xy: Vec2 = Vec2::from_angle(self.seed.as_f32() * PI * 4.) * t;
         Vec3::new(xy.x, z, xy.y)
     }
 
@@ -119,7 +119,7 @@ impl ProjectileSystem for MainSpawner {
         result
     }
 
-    fn build_particle(&self, seed: f32) -> Self::Projectile {
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
         MainParticle {
             seed,
             life_time: 0.,
@@ -0,0 +1,185 @@
+use bevy::{
+    math::{Affine3A, Vec3},
+    prelude::{Component, Query, ResMut, Resource},
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::{Projectile, ProjectileBuffer};
+
+/// How the strength of a [`ProjectileAffector`] decreases with distance.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Falloff {
+    /// Full strength inside the radius.
+    #[default]
+    None,
+    /// Linearly decrease to zero at the radius.
+    Linear,
+    /// Smoothly decrease to zero at the radius.
+    Smooth,
+    /// Proportional to `1 / (1 + distance^2)`, cut off at the radius.
+    InverseSquare,
+}
+
+impl Falloff {
+    /// Obtain the strength multiplier at a distance.
+    pub fn factor(&self, distance: f32, radius: f32) -> f32 {
+        if distance >= radius {
+            return 0.;
+        }
+        let t = if radius.is_finite() {
+            distance / radius
+        } else {
+            0.
+        };
+        match self {
+            Falloff::None => 1.,
+            Falloff::Linear => 1. - t,
+            Falloff::Smooth => 1. - t * t * (3. - 2. * t),
+            Falloff::InverseSquare => 1. / (1. + distance * distance),
+        }
+    }
+}
+
+/// Shape of the force applied by a [`ProjectileAffector`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AffectorKind {
+    /// Accelerate along local `-Z`.
+    Wind,
+    /// Accelerate towards the affector, repels if strength is negative.
+    Point,
+    /// Accelerate around local `Y`.
+    Vortex,
+    /// Remove `strength` portion of velocity per second.
+    Drag,
+    /// Accelerate in a direction varying with position.
+    Turbulence { frequency: f32 },
+}
+
+/// A force field that accelerates [`PhysicsProjectile`](crate::PhysicsProjectile)s,
+/// uses the entity's [`GlobalTransform`].
+///
+/// Applied to all clusters before each step,
+/// projectiles opt in via [`Projectile::as_physics_mut`].
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[require(Transform)]
+pub struct ProjectileAffector {
+    /// Shape of the force.
+    pub kind: AffectorKind,
+    /// Acceleration at full strength.
+    pub strength: f32,
+    /// Distance from the affector beyond which it has no effect.
+    pub radius: f32,
+    /// Decrease of strength with distance.
+    pub falloff: Falloff,
+}
+
+impl ProjectileAffector {
+    /// Create an affector with infinite radius and no falloff.
+    pub const fn new(kind: AffectorKind, strength: f32) -> Self {
+        Self {
+            kind,
+            strength,
+            radius: f32::INFINITY,
+            falloff: Falloff::None,
+        }
+    }
+
+    /// Set the radius and falloff.
+    pub const fn with_falloff(mut self, radius: f32, falloff: Falloff) -> Self {
+        self.radius = radius;
+        self.falloff = falloff;
+        self
+    }
+}
+
+/// A [`ProjectileAffector`] in world space.
+#[derive(Debug, Clone, Copy)]
+pub struct WorldAffector {
+    pub affector: ProjectileAffector,
+    pub translation: Vec3,
+    /// Local `-Z` for [`AffectorKind::Wind`], local `Y` otherwise.
+    pub direction: Vec3,
+}
+
+/// All [`ProjectileAffector`]s in the world, collected at the start of [`ProjectileSet::Simulate`](crate::ProjectileSet::Simulate).
+#[derive(Debug, Default, Resource)]
+pub struct ProjectileAffectors(pub Vec<WorldAffector>);
+
+pub(crate) fn collect_projectile_affectors(
+    mut affectors: ResMut<ProjectileAffectors>,
+    query: Query<(&ProjectileAffector, &GlobalTransform)>,
+) {
+    affectors.0.clear();
+    affectors
+        .0
+        .extend(query.iter().map(|(affector, transform)| WorldAffector {
+            affector: *affector,
+            translation: transform.translation(),
+            direction: match affector.kind {
+                AffectorKind::Wind => transform.forward().as_vec3(),
+                _ => transform.up().as_vec3(),
+            },
+        }));
+}
+
+/// A cheap smooth pseudo random direction field.
+fn turbulence(p: Vec3) -> Vec3 {
+    Vec3::new(
+        p.y.sin() + (p.z * 1.3).cos(),
+        p.z.sin() + (p.x * 1.3).cos(),
+        p.x.sin() + (p.y * 1.3).cos(),
+    ) * 0.5
+}
+
+impl WorldAffector {
+    /// Obtain the new velocity of a projectile after `dt`.
+    pub fn apply(&self, position: Vec3, velocity: Vec3, dt: f32) -> Vec3 {
+        let offset = self.translation - position;
+        let fac = self
+            .affector
+            .falloff
+            .factor(offset.length(), self.affector.radius);
+        if fac <= 0. {
+            return velocity;
+        }
+        let strength = self.affector.strength * fac;
+        match self.affector.kind {
+            AffectorKind::Wind => velocity + self.direction * strength * dt,
+            AffectorKind::Point => velocity + offset.normalize_or_zero() * strength * dt,
+            AffectorKind::Vortex => {
+                velocity + self.direction.cross(-offset).normalize_or_zero() * strength * dt
+            }
+            AffectorKind::Drag => velocity * (1. - (strength * dt).min(1.)),
+            AffectorKind::Turbulence { frequency } => {
+                velocity + turbulence(position * frequency) * strength * dt
+            }
+        }
+    }
+}
+
+/// Apply affectors to velocities of particles that have not expired.
+pub(crate) fn apply_affectors<P: Projectile>(
+    buffer: &mut ProjectileBuffer,
+    affectors: &[WorldAffector],
+    to_world: Affine3A,
+    dt: f32,
+) {
+    if affectors.is_empty() {
+        return;
+    }
+    let to_local = to_world.inverse();
+    for item in buffer.get_mut::<P>() {
+        if item.is_expired() {
+            continue;
+        }
+        let Some(physics) = item.as_physics_mut() else {
+            continue;
+        };
+        let position = to_world.transform_point3(physics.position());
+        let velocity = affectors.iter().fold(
+            to_world.transform_vector3(physics.velocity()),
+            |velocity, affector| affector.apply(position, velocity, dt),
+        );
+        physics.set_velocity(to_local.transform_vector3(velocity));
+    }
+}
@@ -0,0 +1,1012 @@
+//! Data driven emitters loaded from `.emitter.ron` files.
+use std::{f32::consts::TAU, fmt::Display, sync::Arc};
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetEvent, AssetId, AssetLoader, Assets, Handle, LoadContext},
+    color::{ColorToComponents, Srgba},
+    hierarchy::{BuildChildren, DespawnRecursiveExt},
+    math::{Vec3, Vec4},
+    prelude::{
+        Commands, Component, DetectChanges, Entity, EventReader, Mesh3d, Query, Ref, Res, Without,
+    },
+    reflect::TypePath,
+    transform::components::Transform,
+    utils::HashSet,
+};
+use serde::Deserialize;
+
+use crate::{
+    expr::{Expr, ExprVars},
+    templates::{ExpDecayTrail, WidthCurve},
+    util::{random_cone, spawn_rate, EmitShape},
+    ErasedEventParticleSystem, ExpirationState, ParticleBufferStrategy, ParticleSeed,
+    PhysicsProjectile, Projectile, ProjectileBuffer, ProjectileCluster, ProjectileEventBuffer,
+    ProjectileEventType, ProjectileParent, ProjectileSystem,
+};
+
+/// Number of points in trails of [`EmitterParticle`]s.
+pub const EMITTER_TRAIL_POINTS: usize = 8;
+
+/// Shortest [`EmitterAsset::duration`] accepted by [`EmitterAssetLoader`].
+pub const MIN_EMITTER_DURATION: f32 = 0.001;
+
+/// Highest spawn rate per second, [`EmitterAsset::rate_expr`] is clamped to this.
+pub const MAX_EMITTER_RATE: f32 = 1_000_000.;
+
+/// A value sampled once per particle.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum EmitterValue {
+    /// A constant value.
+    Constant(f32),
+    /// A uniformly distributed value in `min..max`.
+    Range(f32, f32),
+    /// An expression evaluated when the particle spawns.
+    Expr(Expr),
+}
+
+impl Default for EmitterValue {
+    fn default() -> Self {
+        EmitterValue::Constant(1.)
+    }
+}
+
+impl EmitterValue {
+    /// Sample with a random `0.0..1.0` value, expressions are evaluated with `vars`.
+    pub fn sample(&self, fac: f32, vars: &ExprVars) -> f32 {
+        match self {
+            EmitterValue::Constant(value) => *value,
+            EmitterValue::Range(min, max) => min + (max - min) * fac,
+            EmitterValue::Expr(expr) => expr.eval(vars),
+        }
+    }
+}
+
+/// A piecewise linear curve of `(fac, value)` keys sorted by `fac`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct EmitterCurve(pub Vec<(f32, f32)>);
+
+impl EmitterCurve {
+    /// Sample the curve, `1.0` if empty.
+    pub fn sample(&self, fac: f32) -> f32 {
+        sample_keys(&self.0, fac, |a, b, t| a + (b - a) * t).unwrap_or(1.)
+    }
+}
+
+/// A piecewise linear gradient of `(fac, rgba)` keys sorted by `fac`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct EmitterGradient(pub Vec<(f32, [f32; 4])>);
+
+impl EmitterGradient {
+    /// Sample the gradient, white if empty.
+    pub fn sample(&self, fac: f32) -> Vec4 {
+        sample_keys(&self.0, fac, |a, b, t| {
+            Vec4::from_array(a).lerp(Vec4::from_array(b), t).to_array()
+        })
+        .map_or(Vec4::ONE, Vec4::from_array)
+    }
+}
+
+fn sample_keys<T: Copy>(keys: &[(f32, T)], fac: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let (first, last) = (keys.first()?, keys.last()?);
+    if fac <= first.0 {
+        return Some(first.1);
+    }
+    if fac >= last.0 {
+        return Some(last.1);
+    }
+    let index = keys.partition_point(|(t, _)| *t <= fac);
+    let ((t0, a), (t1, b)) = (keys[index - 1], keys[index]);
+    let t = if t1 > t0 { (fac - t0) / (t1 - t0) } else { 1. };
+    Some(lerp(a, b, t))
+}
+
+/// Where particles spawn and the direction they move in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub enum EmitterShape {
+    /// Spawn at the origin in random directions.
+    #[default]
+    Point,
+    /// Spawn inside a sphere, moving outwards.
+    Sphere { radius: f32 },
+    /// Spawn inside a disk on the `XZ` plane, moving up.
+    Disk { radius: f32 },
+    /// Spawn at the origin, moving up within `angle` radians.
+    Cone { angle: f32 },
+    /// Spawn inside a box, moving up.
+    Box { half_size: [f32; 3] },
+}
+
+impl EmitterShape {
+    /// Obtain a position and direction from a seed.
+    pub fn sample(&self, seed: ParticleSeed) -> (Vec3, Vec3) {
+        let shape = match *self {
+            EmitterShape::Point => EmitShape::Point,
+            EmitterShape::Sphere { radius } => EmitShape::Sphere {
+                radius,
+                thickness: 1.,
+            },
+            EmitterShape::Disk { radius } => EmitShape::Disk {
+                radius,
+                inner_radius: 0.,
+                arc: TAU,
+            },
+            EmitterShape::Cone { angle } => {
+                return (Vec3::ZERO, random_cone(Vec3::Y, angle, seed));
+            }
+            EmitterShape::Box { half_size } => EmitShape::Box {
+                half_size: Vec3::from_array(half_size),
+            },
+        };
+        shape.sample(seed)
+    }
+}
+
+/// A burst of particles at a time since the emitter started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct EmitterBurst {
+    pub time: f32,
+    pub count: usize,
+}
+
+/// Trail settings of an emitter.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct EmitterTrail {
+    /// Width of the trail.
+    pub width: f32,
+    /// How fast trail points follow, usually in `10..50`.
+    #[serde(default = "default_trail_decay")]
+    pub decay: f32,
+}
+
+fn default_trail_decay() -> f32 {
+    16.
+}
+
+/// An emitter spawning particles on a parent emitter's events.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SubEmitter {
+    /// Event that triggers the emitter.
+    pub on: ProjectileEventType,
+    /// Number of particles spawned per event.
+    pub count: usize,
+    /// The emitter, its `rate`, `rate_expr` and `bursts` are ignored.
+    pub emitter: EmitterAsset,
+}
+
+/// A declarative emitter description, loaded from `.emitter.ron` files.
+///
+/// Add [`ProjectileEmitter`] to an entity to spawn a [`ProjectileCluster`] running an [`EmitterSystem`],
+/// the cluster is updated in place when the asset is modified.
+///
+/// ```ron
+/// (
+///     rate: 20.0,
+///     bursts: [(time: 0.0, count: 50)],
+///     shape: Cone(angle: 0.4),
+///     lifetime: Range(1.0, 2.0),
+///     speed: Range(4.0, 6.0),
+///     size: Expr("0.5 + seed * 0.5"),
+///     gravity: (0.0, -9.8, 0.0),
+///     size_over_lifetime: ([(0.0, 1.0), (1.0, 0.0)]),
+///     sub_emitters: [(on: FadeOut, count: 10, emitter: (lifetime: Constant(0.5)))],
+/// )
+/// ```
+///
+/// Expressions are compiled on load, see [`expr`](crate::expr) for syntax.
+#[derive(Debug, Clone, PartialEq, Asset, TypePath, Deserialize)]
+#[serde(default)]
+pub struct EmitterAsset {
+    /// Nominal capacity of the particle buffer, grows if exceeded.
+    pub capacity: usize,
+    /// Particles spawned per second, at most [`MAX_EMITTER_RATE`].
+    pub rate: f32,
+    /// Particles spawned per second as an expression of `time`, replaces `rate`.
+    pub rate_expr: Option<Expr>,
+    /// Bursts of particles, repeated if looping.
+    pub bursts: Vec<EmitterBurst>,
+    /// Duration of the emitter, emits forever if `None`, at least [`MIN_EMITTER_DURATION`].
+    pub duration: Option<f32>,
+    /// Restart after `duration`.
+    pub looping: bool,
+    /// Where particles spawn.
+    pub shape: EmitterShape,
+    /// Lifetime of particles in seconds.
+    pub lifetime: EmitterValue,
+    /// Initial speed along the shape's direction.
+    pub speed: EmitterValue,
+    /// Initial velocity as expressions, added to the velocity from `shape` and `speed`.
+    pub velocity_expr: Option<[Expr; 3]>,
+    /// Acceleration applied to particles.
+    pub gravity: [f32; 3],
+    /// Portion of velocity removed per second.
+    pub drag: f32,
+    /// Initial size of particles.
+    pub size: EmitterValue,
+    /// Size multiplier over normalized lifetime.
+    pub size_over_lifetime: EmitterCurve,
+    /// Initial color of particles.
+    pub color: [f32; 4],
+    /// Color of particles as expressions evaluated every frame, replaces `color`.
+    pub color_expr: Option<[Expr; 4]>,
+    /// Color multiplier over normalized lifetime.
+    pub color_over_lifetime: EmitterGradient,
+    /// Enables trails on particles, render via [`TrailMeshOf`](crate::trail::TrailMeshOf).
+    pub trail: Option<EmitterTrail>,
+    /// Emitters spawned as children, triggered by events of this emitter.
+    pub sub_emitters: Vec<SubEmitter>,
+}
+
+impl Default for EmitterAsset {
+    fn default() -> Self {
+        Self {
+            capacity: 256,
+            rate: 0.,
+            rate_expr: None,
+            bursts: Vec::new(),
+            duration: None,
+            looping: false,
+            shape: EmitterShape::Point,
+            lifetime: EmitterValue::Constant(1.),
+            speed: EmitterValue::Constant(1.),
+            velocity_expr: None,
+            gravity: [0.; 3],
+            drag: 0.,
+            size: EmitterValue::Constant(1.),
+            size_over_lifetime: EmitterCurve::default(),
+            color: [1.; 4],
+            color_expr: None,
+            color_over_lifetime: EmitterGradient::default(),
+            trail: None,
+            sub_emitters: Vec::new(),
+        }
+    }
+}
+
+impl EmitterAsset {
+    /// Check values that would stall or flood the emitter, including sub emitters.
+    pub fn validate(&self) -> Result<(), EmitterLoadError> {
+        if !(0. ..=MAX_EMITTER_RATE).contains(&self.rate) {
+            return Err(EmitterLoadError::Invalid(format!(
+                "rate {} is not in 0..={MAX_EMITTER_RATE}",
+                self.rate
+            )));
+        }
+        if let Some(duration) = self.duration {
+            if !(duration >= MIN_EMITTER_DURATION && duration.is_finite()) {
+                return Err(EmitterLoadError::Invalid(format!(
+                    "duration {duration} is not a finite value of at least {MIN_EMITTER_DURATION}"
+                )));
+            }
+        }
+        self.sub_emitters
+            .iter()
+            .try_for_each(|x| x.emitter.validate())
+    }
+
+    /// Obtain the color of a particle before [`EmitterAsset::color_over_lifetime`].
+    fn color_at(&self, vars: &ExprVars) -> Vec4 {
+        match &self.color_expr {
+            Some(exprs) => Vec4::from_array(exprs.each_ref().map(|x| x.eval(vars))),
+            None => Vec4::from_array(self.color),
+        }
+    }
+}
+
+/// Error loading an [`EmitterAsset`].
+#[derive(Debug)]
+pub enum EmitterLoadError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl Display for EmitterLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitterLoadError::Io(e) => write!(f, "Failed to read emitter: {e}"),
+            EmitterLoadError::Ron(e) => write!(f, "Failed to parse emitter: {e}"),
+            EmitterLoadError::Invalid(e) => write!(f, "Invalid emitter: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for EmitterLoadError {}
+
+impl From<std::io::Error> for EmitterLoadError {
+    fn from(value: std::io::Error) -> Self {
+        EmitterLoadError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for EmitterLoadError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        EmitterLoadError::Ron(value)
+    }
+}
+
+/// [`AssetLoader`] for [`EmitterAsset`]s with the extension `.emitter.ron`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmitterAssetLoader;
+
+impl AssetLoader for EmitterAssetLoader {
+    type Asset = EmitterAsset;
+    type Settings = ();
+    type Error = EmitterLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &(),
+        _: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let asset: EmitterAsset = ron::de::from_bytes(&bytes)?;
+        asset.validate()?;
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["emitter.ron"]
+    }
+}
+
+/// A particle of an [`EmitterSystem`].
+#[derive(Debug, Clone, Copy)]
+pub struct EmitterParticle {
+    pub seed: ParticleSeed,
+    /// Number of particles spawned by the emitter before this one.
+    pub index: u32,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub gravity: Vec3,
+    pub drag: f32,
+    /// Time since spawn.
+    pub age: f32,
+    /// Time until the particle fades out.
+    pub lifetime: f32,
+    /// Initial size.
+    pub base_size: f32,
+    /// Size written by [`EmitterSystem`].
+    pub size: f32,
+    /// Color written by [`EmitterSystem`].
+    pub color: Vec4,
+    pub expired: ExpirationState,
+    /// Trail, rendered if `has_trail`.
+    pub trail: ExpDecayTrail<EMITTER_TRAIL_POINTS>,
+    pub has_trail: bool,
+}
+
+impl PhysicsProjectile for EmitterParticle {
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn set_position(&mut self, position: Vec3) {
+        self.position = position
+    }
+
+    fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    fn set_velocity(&mut self, velocity: Vec3) {
+        self.velocity = velocity
+    }
+
+    fn expire(&mut self, state: ExpirationState) {
+        self.expired = state;
+    }
+}
+
+impl Projectile for EmitterParticle {
+    fn get_seed(&self) -> ParticleSeed {
+        self.seed
+    }
+
+    fn get_index(&self) -> u32 {
+        self.index
+    }
+
+    fn get_lifetime(&self) -> f32 {
+        self.age
+    }
+
+    fn get_fac(&self) -> f32 {
+        self.age / self.lifetime
+    }
+
+    fn get_transform(&self) -> Transform {
+        Transform::from_translation(self.position).with_scale(Vec3::splat(self.size))
+    }
+
+    fn get_color(&self) -> Srgba {
+        Srgba::from_vec4(self.color)
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.age += dt;
+        self.velocity += self.gravity * dt;
+        self.velocity *= 1. - (self.drag * dt).min(1.);
+        self.position += self.velocity * dt;
+        if self.has_trail {
+            self.trail.set_first(self.position);
+            self.trail.update(dt);
+        }
+    }
+
+    fn expiration_state(&self) -> ExpirationState {
+        if self.expired.is_expired() {
+            return self.expired;
+        }
+        ExpirationState::fizzle_if(self.age >= self.lifetime)
+    }
+
+    fn trail(&self) -> &[(Vec3, f32)] {
+        if self.has_trail {
+            &self.trail.buffer
+        } else {
+            &[]
+        }
+    }
+
+    fn as_physics_mut(&mut self) -> Option<&mut dyn PhysicsProjectile> {
+        Some(self)
+    }
+}
+
+/// A [`ProjectileSystem`] driven by an [`EmitterAsset`].
+#[derive(Debug, Clone)]
+pub struct EmitterSystem {
+    /// The emitter description, can be replaced while running.
+    pub asset: Arc<EmitterAsset>,
+    /// Time since the emitter started or looped.
+    pub time: f32,
+    /// Event and count if spawned as a [`SubEmitter`].
+    pub trigger: Option<(ProjectileEventType, usize)>,
+    spawn_meta: f32,
+    /// Number of particles spawned.
+    spawned: u32,
+}
+
+impl EmitterSystem {
+    /// Create an emitter from a description.
+    pub fn new(asset: impl Into<Arc<EmitterAsset>>) -> Self {
+        Self {
+            asset: asset.into(),
+            time: 0.,
+            trigger: None,
+            spawn_meta: 0.,
+            spawned: 0,
+        }
+    }
+
+    /// Create an emitter that spawns `count` particles on parent events.
+    pub fn sub_emitter(sub: &SubEmitter) -> Self {
+        Self {
+            trigger: Some((sub.on, sub.count)),
+            ..Self::new(sub.emitter.clone())
+        }
+    }
+
+    /// Build the `index`-th particle at a position relative to the emitter.
+    fn build_at(&self, origin: Vec3, seed: ParticleSeed, index: u32) -> EmitterParticle {
+        let asset = &self.asset;
+        let mut rng = seed.derive(0).rng();
+        let (size_fac, speed_fac, lifetime_fac) = (rng.f32(), rng.f32(), rng.f32());
+        let (position, direction) = asset.shape.sample(seed);
+        let position = origin + position;
+        let mut vars = ExprVars {
+            seed: seed.as_f32(),
+            index,
+            time: self.time,
+            position,
+            ..Default::default()
+        };
+        let mut velocity = direction * asset.speed.sample(speed_fac, &vars);
+        if let Some(exprs) = &asset.velocity_expr {
+            velocity += Vec3::from_array(exprs.each_ref().map(|x| x.eval(&vars)));
+        }
+        vars.velocity = velocity;
+        let lifetime = asset.lifetime.sample(lifetime_fac, &vars).max(f32::EPSILON);
+        let base_size = asset.size.sample(size_fac, &vars);
+        EmitterParticle {
+            seed,
+            index,
+            position,
+            velocity,
+            gravity: Vec3::from_array(asset.gravity),
+            drag: asset.drag,
+            age: 0.,
+            lifetime,
+            base_size,
+            size: base_size,
+            color: asset.color_at(&vars),
+            expired: ExpirationState::None,
+            trail: ExpDecayTrail {
+                buffer: [(position, 0.); EMITTER_TRAIL_POINTS],
+                position_decay: asset.trail.map_or(16., |x| x.decay),
+                width_curve: WidthCurve::Fac(|x| 1. - x),
+                ..Default::default()
+            },
+            has_trail: asset.trail.is_some(),
+        }
+    }
+
+    /// Advance time and obtain the number of particles to emit.
+    fn emit_count(&mut self, dt: f32) -> usize {
+        let asset = self.asset.clone();
+        let rate = asset.rate_expr.as_ref().map_or(asset.rate, |x| {
+            x.eval(&ExprVars {
+                time: self.time,
+                ..Default::default()
+            })
+        });
+        // Unlike `clamp`, maps `NaN` to `0`.
+        let rate = if rate > 0. {
+            rate.min(MAX_EMITTER_RATE)
+        } else {
+            0.
+        };
+        let bursts = |start: f32, end: f32| {
+            asset
+                .bursts
+                .iter()
+                .filter(|x| x.time >= start && x.time < end)
+                .map(|x| x.count)
+                .sum::<usize>()
+        };
+        let end = self.time + dt;
+        let Some(duration) = asset.duration.filter(|x| end >= *x && *x > 0.) else {
+            let count = bursts(self.time, end) + spawn_rate(&mut self.spawn_meta, rate, dt);
+            self.time = end;
+            return count;
+        };
+        let mut count = bursts(self.time, duration);
+        count += spawn_rate(&mut self.spawn_meta, rate, (duration - self.time).max(0.));
+        if !asset.looping {
+            self.time = duration;
+            return count;
+        }
+        // Count whole cycles instead of stepping through them, `duration` may be tiny.
+        let remaining = end - duration;
+        let cycles = (remaining / duration).floor();
+        let rest = remaining.rem_euclid(duration);
+        count = count.saturating_add(bursts(0., duration).saturating_mul(cycles as usize));
+        count = count.saturating_add(spawn_rate(&mut self.spawn_meta, rate, duration * cycles));
+        count = count.saturating_add(bursts(0., rest));
+        count = count.saturating_add(spawn_rate(&mut self.spawn_meta, rate, rest));
+        self.time = rest;
+        count
+    }
+
+    /// Spawn `count` particles at a position relative to the emitter.
+    fn spawn_at(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        origin: Vec3,
+        count: usize,
+        parent: Option<u32>,
+    ) {
+        let mut rng = buffer.rng();
+        let seeds: Vec<_> = (0..count).map(|_| self.rng(&mut rng)).collect();
+        buffer.set_rng(&rng);
+        for seed in &seeds {
+            buffer.record_spawn(*seed, parent);
+        }
+        let start = self.spawned;
+        self.spawned = self.spawned.wrapping_add(count as u32);
+        buffer.extend(
+            seeds
+                .into_iter()
+                .zip(start..)
+                .map(|(seed, index)| self.build_at(origin, seed, index)),
+        );
+    }
+}
+
+impl ProjectileSystem for EmitterSystem {
+    type Projectile = EmitterParticle;
+
+    const STRATEGY: ParticleBufferStrategy = ParticleBufferStrategy::Growable { max: None };
+
+    fn capacity(&self) -> usize {
+        self.asset.capacity
+    }
+
+    /// Particles are spawned in [`ProjectileSystem::on_update`] to number them in order.
+    fn spawn_step(&mut self, _: f32) -> usize {
+        0
+    }
+
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
+        self.build_at(Vec3::ZERO, seed, self.spawned)
+    }
+
+    fn on_update(&mut self, dt: f32, buffer: &mut ProjectileBuffer) {
+        if self.trigger.is_none() && !buffer.is_playing_spawns() {
+            let count = self.emit_count(dt);
+            self.spawn_at(buffer, Vec3::ZERO, count, None);
+        }
+        let asset = &self.asset;
+        let width = asset.trail.map_or(0., |x| x.width);
+        for particle in buffer.get_mut::<EmitterParticle>() {
+            let fac = particle.get_fac();
+            particle.size = particle.base_size * asset.size_over_lifetime.sample(fac);
+            let vars = ExprVars {
+                age: particle.age,
+                fac,
+                seed: particle.seed.as_f32(),
+                index: particle.index,
+                time: self.time,
+                position: particle.position,
+                velocity: particle.velocity,
+            };
+            particle.color = asset.color_at(&vars) * asset.color_over_lifetime.sample(fac);
+            if particle.has_trail {
+                particle
+                    .trail
+                    .buffer
+                    .iter_mut()
+                    .for_each(|(_, w)| *w *= width * particle.size);
+            }
+        }
+    }
+
+    fn as_event_particle_system(&mut self) -> Option<&mut dyn ErasedEventParticleSystem> {
+        if self.trigger.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl ErasedEventParticleSystem for EmitterSystem {
+    fn spawn_on_event(&mut self, buffer: &mut ProjectileBuffer, parent: &ProjectileEventBuffer) {
+        let Some((on, count)) = self.trigger else {
+            return;
+        };
+        if buffer.is_playing_spawns() {
+            for (seed, index, age) in buffer.due_spawns(true) {
+                let Some(event) = parent.get(index.unwrap_or(0) as usize) else {
+                    continue;
+                };
+                let mut particle = self.build_at(event.position, seed, self.spawned);
+                self.spawned = self.spawned.wrapping_add(1);
+                if age > 0. {
+                    particle.update(age);
+                }
+                buffer.extend([particle]);
+            }
+            return;
+        }
+        for (index, event) in parent.iter().enumerate() {
+            if event.event == on {
+                self.spawn_at(buffer, event.position, count, Some(index as u32));
+            }
+        }
+    }
+}
+
+/// Runs an [`EmitterAsset`] on this entity once loaded, reloads when the asset changes.
+///
+/// Sub emitters are spawned as children with [`ProjectileParent`] set to the emitter
+/// triggering them, the [`Mesh3d`] of this entity
+/// is copied to them, while materials need to be added manually, i.e. by querying [`SubEmitterOf`].
+#[derive(Debug, Clone, Default, Component)]
+pub struct ProjectileEmitter(pub Handle<EmitterAsset>);
+
+/// A sub emitter spawned by a [`ProjectileEmitter`], updated in place when the asset changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct SubEmitterOf {
+    /// Entity of the [`ProjectileEmitter`].
+    pub root: Entity,
+    /// Index in [`EmitterAsset::sub_emitters`] of the parent emitter.
+    pub index: usize,
+}
+
+/// Sub emitter entities of all [`ProjectileEmitter`]s.
+type SubEmitterQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static SubEmitterOf,
+        &'static ProjectileParent,
+        &'static mut ProjectileCluster,
+    ),
+    Without<ProjectileEmitter>,
+>;
+
+/// Spawn a cluster for a sub emitter triggered by `parent` and its own sub emitters, as children of `root`.
+fn spawn_sub_emitter(
+    commands: &mut Commands,
+    root: Entity,
+    parent: Entity,
+    index: usize,
+    sub: &SubEmitter,
+    mesh: Option<&Mesh3d>,
+) {
+    let mut child = commands.spawn((
+        ProjectileCluster::new(EmitterSystem::sub_emitter(sub)),
+        ProjectileParent(parent),
+        SubEmitterOf { root, index },
+    ));
+    if let Some(mesh) = mesh {
+        child.insert(mesh.clone());
+    }
+    let child = child.set_parent(root).id();
+    if !sub.emitter.sub_emitters.is_empty() {
+        commands
+            .entity(child)
+            .insert(ProjectileEventBuffer::default());
+    }
+    for (index, nested) in sub.emitter.sub_emitters.iter().enumerate() {
+        spawn_sub_emitter(commands, root, child, index, nested, mesh);
+    }
+}
+
+/// Despawn a sub emitter and sub emitters triggered by it.
+fn despawn_sub_emitter(commands: &mut Commands, subs: &SubEmitterQuery, entity: Entity) {
+    for (child, _, parent, _) in subs.iter() {
+        if parent.0 == entity {
+            despawn_sub_emitter(commands, subs, child);
+        }
+    }
+    commands.entity(entity).despawn_recursive();
+}
+
+/// Update sub emitters triggered by `parent` to match `asset`.
+///
+/// Existing sub emitters are updated in place and keep their particles,
+/// added entries are spawned and removed entries are despawned.
+fn sync_sub_emitters(
+    commands: &mut Commands,
+    subs: &mut SubEmitterQuery,
+    root: Entity,
+    parent: Entity,
+    asset: &EmitterAsset,
+    mesh: Option<&Mesh3d>,
+) {
+    if !asset.sub_emitters.is_empty() {
+        commands
+            .entity(parent)
+            .insert_if_new(ProjectileEventBuffer::default());
+    }
+    let existing: Vec<(Entity, usize)> = subs
+        .iter()
+        .filter(|(_, sub, x, _)| sub.root == root && x.0 == parent)
+        .map(|(entity, sub, _, _)| (entity, sub.index))
+        .collect();
+    for (index, sub) in asset.sub_emitters.iter().enumerate() {
+        let Some((entity, _)) = existing.iter().find(|(_, x)| *x == index) else {
+            spawn_sub_emitter(commands, root, parent, index, sub, mesh);
+            continue;
+        };
+        if let Ok((_, _, _, mut cluster)) = subs.get_mut(*entity) {
+            if let Some(system) = cluster.downcast_mut::<EmitterSystem>() {
+                if *system.asset != sub.emitter {
+                    system.asset = Arc::new(sub.emitter.clone());
+                }
+                system.trigger = Some((sub.on, sub.count));
+            }
+        }
+        sync_sub_emitters(commands, subs, root, *entity, &sub.emitter, mesh);
+    }
+    for (entity, index) in existing {
+        if index >= asset.sub_emitters.len() {
+            despawn_sub_emitter(commands, subs, entity);
+        }
+    }
+}
+
+pub(crate) fn sync_projectile_emitters(
+    mut commands: Commands,
+    assets: Res<Assets<EmitterAsset>>,
+    mut events: EventReader<AssetEvent<EmitterAsset>>,
+    mut emitters: Query<(
+        Entity,
+        Ref<ProjectileEmitter>,
+        Option<&mut ProjectileCluster>,
+        Option<&Mesh3d>,
+    )>,
+    mut subs: SubEmitterQuery,
+) {
+    let modified: HashSet<AssetId<EmitterAsset>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for (entity, emitter, cluster, mesh) in &mut emitters {
+        let running = cluster
+            .as_ref()
+            .is_some_and(|x| x.downcast_ref::<EmitterSystem>().is_some());
+        if running && !emitter.is_changed() && !modified.contains(&emitter.0.id()) {
+            continue;
+        }
+        let Some(asset) = assets.get(&emitter.0) else {
+            continue;
+        };
+        let asset = Arc::new(asset.clone());
+        match cluster.and_then(|x| x.into_inner().downcast_mut::<EmitterSystem>()) {
+            Some(system) => system.asset = asset.clone(),
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(ProjectileCluster::new(EmitterSystem::new(asset.clone())));
+            }
+        }
+        sync_sub_emitters(&mut commands, &mut subs, entity, entity, &asset, mesh);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        asset::{AssetEvent, Assets},
+        math::Vec3,
+        prelude::Entity,
+    };
+
+    use super::{
+        sync_projectile_emitters, EmitterAsset, EmitterBurst, EmitterParticle, EmitterSystem,
+        ProjectileEmitter, SubEmitter, SubEmitterOf,
+    };
+    use crate::{
+        ErasedEventParticleSystem, ParticleSeed, ProjectileBuffer, ProjectileCluster,
+        ProjectileEvent, ProjectileEventBuffer, ProjectileEventType,
+    };
+
+    fn emitter(sub_emitters: Vec<SubEmitter>) -> EmitterAsset {
+        EmitterAsset {
+            sub_emitters,
+            ..Default::default()
+        }
+    }
+
+    fn sub(count: usize) -> SubEmitter {
+        SubEmitter {
+            on: ProjectileEventType::Explode,
+            count,
+            emitter: emitter(Vec::new()),
+        }
+    }
+
+    fn subs(app: &mut App) -> Vec<(Entity, usize, usize)> {
+        let mut query = app
+            .world_mut()
+            .query::<(Entity, &SubEmitterOf, &ProjectileCluster)>();
+        let mut result: Vec<_> = query
+            .iter(app.world())
+            .map(|(entity, sub, cluster)| {
+                let system = cluster.downcast_ref::<EmitterSystem>().unwrap();
+                (entity, sub.index, system.trigger.unwrap().1)
+            })
+            .collect();
+        result.sort_by_key(|(_, index, _)| *index);
+        result
+    }
+
+    #[test]
+    fn reload_keeps_sub_emitters() {
+        let mut app = App::new();
+        app.init_resource::<Assets<EmitterAsset>>();
+        app.add_event::<AssetEvent<EmitterAsset>>();
+        app.add_systems(Update, sync_projectile_emitters);
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<EmitterAsset>>()
+            .add(emitter(vec![sub(1), sub(2)]));
+        app.world_mut().spawn(ProjectileEmitter(handle.clone()));
+        app.update();
+        let before = subs(&mut app);
+        assert_eq!(before.len(), 2);
+
+        let mut assets = app.world_mut().resource_mut::<Assets<EmitterAsset>>();
+        *assets.get_mut(&handle).unwrap() = emitter(vec![sub(3)]);
+        app.world_mut()
+            .send_event(AssetEvent::Modified { id: handle.id() });
+        app.update();
+        assert_eq!(subs(&mut app), [(before[0].0, 0, 3)]);
+
+        let mut assets = app.world_mut().resource_mut::<Assets<EmitterAsset>>();
+        *assets.get_mut(&handle).unwrap() = emitter(vec![sub(3), sub(4)]);
+        app.world_mut()
+            .send_event(AssetEvent::Modified { id: handle.id() });
+        app.update();
+        let after = subs(&mut app);
+        assert_eq!(after.len(), 2);
+        assert_eq!(after[0], (before[0].0, 0, 3));
+        assert_eq!(after[1].2, 4);
+    }
+
+    fn looping(duration: f32, rate: f32, bursts: &[(f32, usize)]) -> EmitterSystem {
+        EmitterSystem::new(EmitterAsset {
+            rate,
+            bursts: bursts
+                .iter()
+                .map(|&(time, count)| EmitterBurst { time, count })
+                .collect(),
+            duration: Some(duration),
+            looping: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn looping_emit_count() {
+        let mut system = looping(1., 10., &[(0., 2), (0.5, 3)]);
+        // Rest of the first cycle, one whole cycle and `0.75` of the next.
+        assert_eq!(system.emit_count(2.75), 15 + 15 + 5 + 7);
+        assert_eq!(system.time, 0.75);
+        assert_eq!(system.emit_count(0.25), 3);
+        assert_eq!(system.time, 0.);
+    }
+
+    #[test]
+    fn tiny_duration_terminates() {
+        let mut system = looping(5e-10, 0., &[(0., 1)]);
+        let count = system.emit_count(0.016);
+        assert!(count > 30_000_000, "{count}");
+        assert!(system.time < 5e-10);
+    }
+
+    #[test]
+    fn validate() {
+        let asset = |rate: f32, duration: Option<f32>| EmitterAsset {
+            rate,
+            duration,
+            ..Default::default()
+        };
+        assert!(asset(0., None).validate().is_ok());
+        assert!(asset(1e6, Some(0.001)).validate().is_ok());
+        for duration in [0., -1., 1e-9, f32::NAN, f32::INFINITY] {
+            assert!(asset(1., Some(duration)).validate().is_err(), "{duration}");
+        }
+        for rate in [-1., 1e7, f32::NAN, f32::INFINITY] {
+            assert!(asset(rate, None).validate().is_err(), "{rate}");
+        }
+        let mut nested = sub(1);
+        nested.emitter.duration = Some(0.);
+        assert!(emitter(vec![nested]).validate().is_err());
+    }
+
+    #[test]
+    fn sub_emitter_playback() {
+        let mut events = ProjectileEventBuffer::default();
+        events.push(ProjectileEvent {
+            event: ProjectileEventType::Explode,
+            seed: ParticleSeed(0),
+            index: 0,
+            lifetime: 0.,
+            position: Vec3::ONE,
+            tangent: Vec3::ZERO,
+            normal: Vec3::ZERO,
+        });
+        let mut recorder = EmitterSystem::sub_emitter(&sub(3));
+        let mut recorded = ProjectileBuffer::new_retain::<EmitterParticle>(8);
+        recorded.record_spawns();
+        recorder.spawn_on_event(&mut recorded, &events);
+        let spawns = recorded.take_spawns();
+        assert_eq!(spawns.len(), 3);
+
+        let mut player = EmitterSystem::sub_emitter(&sub(3));
+        let mut played = ProjectileBuffer::new_retain::<EmitterParticle>(8);
+        played.play_spawns();
+        played.push_spawns(spawns);
+        player.spawn_on_event(&mut played, &events);
+        player.spawn_on_event(&mut played, &events);
+        let seeds = |buffer: &ProjectileBuffer| {
+            buffer
+                .get::<EmitterParticle>()
+                .iter()
+                .map(|x| (x.seed, x.position))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(seeds(&played), seeds(&recorded));
+    }
+}
@@ -13,10 +13,14 @@ use bevy::{
         mesh::VertexBufferLayout,
         render_resource::{VertexAttribute, VertexFormat, VertexStepMode},
     },
+    transform::components::Transform,
 };
 use bytemuck::{Pod, Zeroable};
 
-use crate::{Projectile, ProjectileInstanceBuffer};
+use crate::{
+    neighbours::NeighbourScratch, replication::SpawnReplication, HandleTable, Projectile,
+    ProjectileHandle, ProjectileInstanceBuffer,
+};
 
 fn validate<T>() {
     if !matches!(align_of::<T>(), 1 | 2 | 4 | 8 | 16) {
@@ -35,6 +39,41 @@ pub enum ParticleBufferStrategy {
     /// Should only be used if lifetimes of particles are constant,
     /// and capacity is well predicted.
     RingBuffer,
+    /// Same as `Retain`, but reallocates when full instead of discarding new particles.
+    ///
+    /// Capacity doubles up to `max` if specified,
+    /// and halves after a sustained period of low usage,
+    /// but never below the initial capacity.
+    Growable { max: Option<usize> },
+}
+
+/// What happens when spawning into a full [`ProjectileBuffer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the new particle.
+    #[default]
+    DropNew,
+    /// Replace the particle with the largest [`Projectile::get_lifetime`].
+    ReplaceOldest,
+    /// Replace the particle with the largest [`Projectile::get_fac`],
+    /// i.e. the one closest to expiring if `fac` is normalized lifetime.
+    ReplaceExpiring,
+    /// Panic in debug builds, discard the new particle in release builds.
+    Panic,
+}
+
+/// Number of consecutive steps below a quarter of capacity before a growable buffer shrinks.
+const SHRINK_AFTER_STEPS: u32 = 300;
+
+/// State of a buffer in [`ParticleBufferStrategy::Growable`] mode.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Growth {
+    /// Initial capacity, the buffer does not shrink below this.
+    min: usize,
+    /// Hard maximum of capacity.
+    max: Option<usize>,
+    /// Number of consecutive steps with low usage.
+    low_usage: u32,
 }
 
 #[doc(hidden)]
@@ -71,11 +110,18 @@ pub struct DefaultInstanceBuffer {
 
 impl<T: Projectile> From<&T> for DefaultInstanceBuffer {
     fn from(x: &T) -> Self {
-        let transform = x.get_transform().compute_matrix();
+        DefaultInstanceBuffer::from_transform(x, x.get_transform())
+    }
+}
+
+impl DefaultInstanceBuffer {
+    /// Create an instance buffer from a projectile, with its transform replaced.
+    pub fn from_transform<T: Projectile>(x: &T, transform: Transform) -> Self {
+        let transform = transform.compute_matrix();
         DefaultInstanceBuffer {
             index: x.get_index(),
             lifetime: x.get_lifetime(),
-            seed: x.get_seed(),
+            seed: x.get_seed().as_f32(),
             fac: x.get_fac(),
             color: x.get_color().to_vec4(),
             transform_x: transform.row(0),
@@ -168,6 +214,63 @@ pub enum ParticleBufferType {
     RingBuffer(TypeId),
 }
 
+/// Per particle data that must follow particles when they are moved around.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Tracking {
+    /// Transforms of particles before the last step.
+    pub(crate) previous: Option<Vec<Transform>>,
+    /// Stable handles of particles, enabled on first use.
+    pub(crate) handles: Option<HandleTable>,
+}
+
+impl Tracking {
+    /// Record data of the particle at `index`, if tracked.
+    pub(crate) fn record(&mut self, index: usize, transform: impl FnOnce() -> Transform) {
+        if let Some(previous) = &mut self.previous {
+            if index < previous.len() {
+                previous[index] = transform();
+            } else {
+                previous.resize(index, Transform::IDENTITY);
+                previous.push(transform());
+            }
+        }
+        if let Some(handles) = &mut self.handles {
+            handles.assign(index);
+        }
+    }
+
+    /// Obtain `len` previous transforms for writing, if tracked.
+    pub(crate) fn previous_mut(&mut self, len: usize) -> Option<&mut [Transform]> {
+        let previous = self.previous.as_mut()?;
+        if previous.len() < len {
+            previous.resize(len, Transform::IDENTITY);
+        }
+        Some(&mut previous[..len])
+    }
+
+    /// Swap data of two particles.
+    pub(crate) fn swap(&mut self, a: usize, b: usize) {
+        if let Some(previous) = &mut self.previous {
+            if a.max(b) < previous.len() {
+                previous.swap(a, b);
+            }
+        }
+        if let Some(handles) = &mut self.handles {
+            handles.swap(a, b);
+        }
+    }
+
+    /// Remove data of particles beyond `len`.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if let Some(previous) = &mut self.previous {
+            previous.truncate(len);
+        }
+        if let Some(handles) = &mut self.handles {
+            handles.truncate(len);
+        }
+    }
+}
+
 /// Type erased buffer for particles.
 #[derive(Debug, Component, Default)]
 pub struct ProjectileBuffer {
@@ -175,7 +278,7 @@ pub struct ProjectileBuffer {
     pub(crate) particle_type: ParticleBufferType,
     /// Allocated buffer.
     pub(crate) buffer: Box<[Align16MaybeUninit]>,
-    /// Tracks number of particles.
+    /// Tracks number of particles, in ring mode the number of live particles.
     pub(crate) len: usize,
     /// Maximum number of particles possible.
     pub(crate) capacity: usize,
@@ -185,6 +288,26 @@ pub struct ProjectileBuffer {
     pub(crate) ring_capacity: usize,
     /// Allocation of extracted particles on the render world.
     pub(crate) extracted_allocation: Mutex<Arc<ErasedExtractBuffer>>,
+    /// Data tracked alongside particles.
+    pub(crate) tracking: Tracking,
+    /// Fixed timestep: time not yet simulated.
+    pub(crate) accumulated: f32,
+    /// Fixed timestep: blend factor between previous and current transforms on extraction.
+    pub(crate) interpolation: Option<f32>,
+    /// Time simulated in the last frame.
+    pub(crate) delta: f32,
+    /// State of the cluster's random number stream.
+    pub(crate) rng: u64,
+    /// Growable: growth state, `None` if not growable.
+    pub(crate) growth: Option<Growth>,
+    /// What to do when spawning into a full buffer.
+    pub(crate) overflow: OverflowPolicy,
+    /// Number of particles lost to overflow.
+    pub(crate) dropped: usize,
+    /// Spawn recording or playback state.
+    pub(crate) replication: Option<SpawnReplication>,
+    /// Reused allocations of the neighbour pass, not part of the buffer's state.
+    pub(crate) neighbours: NeighbourScratch,
 }
 
 impl ProjectileBuffer {
@@ -211,9 +334,30 @@ impl ProjectileBuffer {
             ptr: 0,
             ring_capacity: 0,
             extracted_allocation: Default::default(),
+            tracking: Default::default(),
+            accumulated: 0.,
+            interpolation: None,
+            delta: 0.,
+            rng: 0,
+            growth: None,
+            overflow: OverflowPolicy::DropNew,
+            dropped: 0,
+            replication: None,
+            neighbours: Default::default(),
         }
     }
 
+    /// Create a buffer in retain mode that grows when full, up to `max` particles if specified.
+    pub fn new_growable<T: Projectile>(nominal_capacity: usize, max: Option<usize>) -> Self {
+        let mut result = Self::new_retain::<T>(nominal_capacity);
+        result.growth = Some(Growth {
+            min: result.capacity,
+            max,
+            low_usage: 0,
+        });
+        result
+    }
+
     /// Create a buffer in ring buffer mode.
     pub fn new_ring<T: Projectile>(nominal_capacity: usize) -> Self {
         validate::<T>();
@@ -227,6 +371,16 @@ impl ProjectileBuffer {
             ptr: 0,
             ring_capacity: 0,
             extracted_allocation: Default::default(),
+            tracking: Default::default(),
+            accumulated: 0.,
+            interpolation: None,
+            delta: 0.,
+            rng: 0,
+            growth: None,
+            overflow: OverflowPolicy::DropNew,
+            dropped: 0,
+            replication: None,
+            neighbours: Default::default(),
         }
     }
 
@@ -283,7 +437,263 @@ impl ProjectileBuffer {
         }
     }
 
-    /// Extends items into the buffer, overflow will be discarded.
+    /// Returns `[..len]` or `[..ring_capacity]` alongside tracked data.
+    ///
+    /// # Panics
+    ///
+    /// If type mismatch or in `uninit` mode.
+    pub(crate) fn get_mut_tracked<T: Projectile>(&mut self) -> (&mut [T], &mut Tracking) {
+        let len = self.get::<T>().len();
+        // Safety: type and length are checked by `get`.
+        let slice = unsafe { slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut T, len) };
+        (slice, &mut self.tracking)
+    }
+
+    /// Enable or disable tracking transforms of particles before the last step.
+    pub(crate) fn track_previous(&mut self, enabled: bool) {
+        match (enabled, &self.tracking.previous) {
+            (true, None) => self.tracking.previous = Some(Vec::new()),
+            (false, Some(_)) => self.tracking.previous = None,
+            _ => (),
+        }
+    }
+
+    /// Returns the maximum number of particles the buffer can currently hold.
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Move particles to a new allocation, `nominal_capacity` must not be less than `len`.
+    fn reallocate<T: Projectile>(&mut self, nominal_capacity: usize) {
+        let real_capacity = (nominal_capacity * size_of::<T>()).div_ceil(16);
+        let used = (self.len * size_of::<T>()).div_ceil(16);
+        let mut buffer: Box<[Align16MaybeUninit]> =
+            vec![Align16MaybeUninit::uninit(); real_capacity].into();
+        buffer[..used].copy_from_slice(&self.buffer[..used]);
+        self.buffer = buffer;
+        self.capacity = real_capacity * 16 / size_of::<T>();
+    }
+
+    /// Try to increase capacity if growable, returns `false` if at maximum capacity.
+    fn grow<T: Projectile>(&mut self) -> bool {
+        let Some(growth) = self.growth else {
+            return false;
+        };
+        let max = growth.max.unwrap_or(usize::MAX);
+        if self.capacity >= max {
+            return false;
+        }
+        self.reallocate::<T>((self.capacity * 2).max(16).min(max));
+        self.capacity > self.len
+    }
+
+    /// Halve capacity if growable and usage stayed below a quarter for a while.
+    pub(crate) fn shrink_unused<T: Projectile>(&mut self) {
+        let Some(growth) = &mut self.growth else {
+            return;
+        };
+        if self.len * 4 >= self.capacity || self.capacity <= growth.min {
+            growth.low_usage = 0;
+            return;
+        }
+        growth.low_usage += 1;
+        if growth.low_usage < SHRINK_AFTER_STEPS {
+            return;
+        }
+        growth.low_usage = 0;
+        let capacity = (self.capacity / 2).max(growth.min);
+        self.reallocate::<T>(capacity);
+    }
+
+    /// Reset the cluster's random number stream with a seed.
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.rng = seed;
+    }
+
+    /// Obtain a copy of the cluster's random number stream,
+    /// write it back via [`ProjectileBuffer::set_rng`] to advance the stream.
+    pub fn rng(&self) -> fastrand::Rng {
+        fastrand::Rng::with_seed(self.rng)
+    }
+
+    /// Set the state of the cluster's random number stream.
+    pub fn set_rng(&mut self, rng: &fastrand::Rng) {
+        self.rng = rng.get_seed();
+    }
+
+    /// Returns the time simulated in the last frame.
+    pub const fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// Returns the interpolation factor used for rendering, if running in fixed timestep mode.
+    pub const fn interpolation(&self) -> Option<f32> {
+        self.interpolation
+    }
+
+    /// Obtain the stable handle of the particle at `index`,
+    /// the index of [`ProjectileBuffer::get`].
+    ///
+    /// Handles are tracked from the first call to this function.
+    pub fn handle(&mut self, index: usize) -> Option<ProjectileHandle> {
+        let len = match self.particle_type {
+            ParticleBufferType::Uninit => return None,
+            ParticleBufferType::Retain(_) => self.len,
+            ParticleBufferType::RingBuffer(_) => self.ring_capacity,
+        };
+        if index >= len {
+            return None;
+        }
+        self.tracking
+            .handles
+            .get_or_insert_with(|| HandleTable::new(len))
+            .handle(index)
+    }
+
+    /// Obtain the index of a handle if the particle is still in the buffer.
+    fn handle_index<T: Projectile>(&self, handle: ProjectileHandle) -> Option<usize> {
+        let index = self.tracking.handles.as_ref()?.index(handle)?;
+        (index < self.get::<T>().len()).then_some(index)
+    }
+
+    /// Returns `true` if the particle of a handle is alive and not expired.
+    ///
+    /// # Panics
+    ///
+    /// If type mismatch or in `uninit` mode.
+    pub fn is_alive<T: Projectile>(&self, handle: ProjectileHandle) -> bool {
+        self.get_by_handle::<T>(handle).is_some()
+    }
+
+    /// Obtain a particle by handle, returns `None` if it has been removed or has expired.
+    ///
+    /// # Panics
+    ///
+    /// If type mismatch or in `uninit` mode.
+    pub fn get_by_handle<T: Projectile>(&self, handle: ProjectileHandle) -> Option<&T> {
+        let index = self.handle_index::<T>(handle)?;
+        self.get::<T>().get(index).filter(|x| !x.is_expired())
+    }
+
+    /// Obtain a mutable particle by handle, returns `None` if it has been removed or has expired.
+    ///
+    /// # Panics
+    ///
+    /// If type mismatch or in `uninit` mode.
+    pub fn get_mut_by_handle<T: Projectile>(&mut self, handle: ProjectileHandle) -> Option<&mut T> {
+        let index = self.handle_index::<T>(handle)?;
+        self.get_mut::<T>()
+            .get_mut(index)
+            .filter(|x| !x.is_expired())
+    }
+
+    /// Remove a particle by handle without emitting events,
+    /// returns `false` if it has already been removed.
+    ///
+    /// Not supported in ring buffer mode, where this always returns `false`.
+    ///
+    /// # Panics
+    ///
+    /// If type mismatch or in `uninit` mode.
+    pub fn kill<T: Projectile>(&mut self, handle: ProjectileHandle) -> bool {
+        if matches!(self.particle_type, ParticleBufferType::RingBuffer(_)) {
+            return false;
+        }
+        let Some(index) = self.handle_index::<T>(handle) else {
+            return false;
+        };
+        let last = self.len - 1;
+        let (buf, tracking) = self.get_mut_tracked::<T>();
+        buf.swap(index, last);
+        tracking.swap(index, last);
+        tracking.truncate(last);
+        self.len = last;
+        true
+    }
+
+    /// Write a particle to a slot.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be the particle type and `index` must be less than `capacity`.
+    unsafe fn write<T: Projectile>(&mut self, index: usize, item: T) {
+        (self.buffer.as_mut_ptr() as *mut MaybeUninit<T>)
+            .add(index)
+            .write(MaybeUninit::new(item));
+    }
+
+    /// Returns `true` if the slot at `index` holds a particle that has not despawned.
+    fn is_live<T: Projectile>(&self, index: usize) -> bool {
+        self.get::<T>()
+            .get(index)
+            .is_some_and(|x| !x.should_despawn())
+    }
+
+    /// Handle a particle spawned into a full buffer,
+    /// returns the slot to overwrite according to the [`OverflowPolicy`].
+    ///
+    /// Only discarded or replaced live particles are counted as dropped,
+    /// in ring mode replacing a dead particle increases `len`.
+    fn overflow_slot<T: Projectile>(&mut self) -> Option<usize> {
+        let key: fn(&T) -> f32 = match self.overflow {
+            OverflowPolicy::DropNew => {
+                self.dropped += 1;
+                return None;
+            }
+            OverflowPolicy::Panic => {
+                if cfg!(debug_assertions) {
+                    panic!("ProjectileBuffer overflowed at capacity {}.", self.capacity)
+                }
+                self.dropped += 1;
+                return None;
+            }
+            OverflowPolicy::ReplaceOldest => |x| x.get_lifetime(),
+            OverflowPolicy::ReplaceExpiring => |x| x.get_fac(),
+        };
+        let Some(index) = self
+            .get::<T>()
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| key(a).total_cmp(&key(b)))
+            .map(|(index, _)| index)
+        else {
+            self.dropped += 1;
+            return None;
+        };
+        if self.is_live::<T>(index) {
+            self.dropped += 1;
+        } else if matches!(self.particle_type, ParticleBufferType::RingBuffer(_)) {
+            self.len += 1;
+        }
+        Some(index)
+    }
+
+    /// Remove all particles, keeps the allocation.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.ptr = 0;
+        self.ring_capacity = 0;
+        self.tracking.truncate(0);
+    }
+
+    /// Set the [`OverflowPolicy`] of the buffer.
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// Returns the number of particles lost to overflow since creation or the last
+    /// [`ProjectileBuffer::reset_dropped`], either discarded on spawn or replaced while alive.
+    pub const fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Reset the counter of particles lost to overflow.
+    pub fn reset_dropped(&mut self) {
+        self.dropped = 0;
+    }
+
+    /// Extends items into the buffer, overflow is handled by the buffer's [`OverflowPolicy`].
     ///
     /// # Panics
     ///
@@ -295,41 +705,237 @@ impl ProjectileBuffer {
                 if id != TypeId::of::<T>() {
                     panic!("Type ID mismatch!")
                 }
-                let slice = unsafe {
-                    slice::from_raw_parts_mut(
-                        self.buffer.as_mut_ptr() as *mut MaybeUninit<T>,
-                        self.capacity,
-                    )
-                };
                 for item in ext {
-                    if self.len >= slice.len() {
+                    let index = if self.len < self.capacity || self.grow::<T>() {
+                        self.len += 1;
+                        self.len - 1
+                    } else if let Some(index) = self.overflow_slot::<T>() {
+                        index
+                    } else {
                         continue;
-                    }
-                    slice[self.len] = MaybeUninit::new(item);
-                    self.len += 1;
+                    };
+                    self.tracking.record(index, || item.get_transform());
+                    // Safety: type is checked and `index` is less than `capacity`.
+                    unsafe { self.write(index, item) };
                 }
             }
             ParticleBufferType::RingBuffer(id) => {
                 if id != TypeId::of::<T>() {
                     panic!("Type ID mismatch!")
                 }
-                let slice = unsafe {
-                    slice::from_raw_parts_mut(
-                        self.buffer.as_mut_ptr() as *mut MaybeUninit<T>,
-                        self.capacity,
-                    )
-                };
+                // Reuse slots from `ptr` onwards not occupied by a live particle,
+                // each slot is visited at most once per call.
+                let mut cursor = self.ptr;
+                let mut unvisited = self.capacity;
                 for item in ext {
-                    if self.len == self.capacity {
-                        continue;
-                    }
-                    if self.ring_capacity < slice.len() && self.ptr > self.ring_capacity {
-                        self.ring_capacity += 1;
+                    let mut free = None;
+                    while unvisited > 0 && free.is_none() {
+                        if !self.is_live::<T>(cursor) {
+                            free = Some(cursor);
+                        }
+                        cursor = (cursor + 1) % self.capacity;
+                        unvisited -= 1;
                     }
-                    slice[self.ptr] = MaybeUninit::new(item);
-                    self.ptr = (self.ptr + 1) % slice.len();
+                    let index = if let Some(index) = free {
+                        self.len += 1;
+                        index
+                    } else if let Some(index) = self.overflow_slot::<T>() {
+                        index
+                    } else {
+                        continue;
+                    };
+                    self.ptr = (index + 1) % self.capacity;
+                    self.ring_capacity = self.ring_capacity.max(index + 1);
+                    self.tracking.record(index, || item.get_transform());
+                    // Safety: type is checked and `index` is less than `capacity`.
+                    unsafe { self.write(index, item) };
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::transform::components::Transform;
+
+    use super::{OverflowPolicy, ProjectileBuffer, SHRINK_AFTER_STEPS};
+    use crate::{ExpirationState, Projectile};
+
+    #[derive(Debug, Clone, Copy)]
+    struct Aged {
+        id: u32,
+        age: f32,
+        dead: bool,
+    }
+
+    impl Aged {
+        fn new(id: u32) -> Self {
+            Self {
+                id,
+                age: 0.,
+                dead: false,
+            }
+        }
+    }
+
+    impl Projectile for Aged {
+        fn get_lifetime(&self) -> f32 {
+            self.age
+        }
+
+        fn get_transform(&self) -> Transform {
+            Transform::IDENTITY
+        }
+
+        fn update(&mut self, dt: f32) {
+            self.age += dt;
+        }
+
+        fn expiration_state(&self) -> ExpirationState {
+            ExpirationState::fizzle_if(self.dead)
+        }
+    }
+
+    fn ids(buffer: &ProjectileBuffer) -> Vec<u32> {
+        buffer.get::<Aged>().iter().map(|x| x.id).collect()
+    }
+
+    /// Kill particles and update `len` like a simulation step would.
+    fn kill(buffer: &mut ProjectileBuffer, ids: &[u32]) {
+        for item in buffer.get_mut::<Aged>() {
+            item.age += 1.;
+            item.dead |= ids.contains(&item.id);
+        }
+        buffer.len = buffer.get::<Aged>().iter().filter(|x| !x.dead).count();
+    }
+
+    #[test]
+    fn ring_wraparound() {
+        let mut buffer = ProjectileBuffer::new_ring::<Aged>(4);
+        let capacity = buffer.capacity() as u32;
+        buffer.extend((0..capacity).map(Aged::new));
+        assert_eq!(buffer.len, capacity as usize);
+        assert_eq!(ids(&buffer), (0..capacity).collect::<Vec<_>>());
+        assert_eq!(buffer.dropped(), 0);
+
+        buffer.extend([Aged::new(100)]);
+        assert_eq!(buffer.len, capacity as usize);
+        assert_eq!(buffer.dropped(), 1);
+
+        kill(&mut buffer, &[0, 1]);
+        buffer.extend([Aged::new(101), Aged::new(102)]);
+        assert_eq!(buffer.len, capacity as usize);
+        assert_eq!(&ids(&buffer)[..3], &[101, 102, 2]);
+        assert_eq!(buffer.dropped(), 1);
+    }
+
+    #[test]
+    fn ring_replace_oldest() {
+        let mut buffer = ProjectileBuffer::new_ring::<Aged>(4)
+            .with_overflow_policy(OverflowPolicy::ReplaceOldest);
+        let capacity = buffer.capacity() as u32;
+        for id in 0..capacity {
+            buffer.extend([Aged::new(id)]);
+            kill(&mut buffer, &[]);
+        }
+        buffer.extend([Aged::new(100)]);
+        assert_eq!(ids(&buffer)[0], 100);
+        assert_eq!(buffer.len, capacity as usize);
+        assert_eq!(buffer.dropped(), 1);
+
+        kill(&mut buffer, &[2]);
+        buffer.extend([Aged::new(101)]);
+        assert_eq!(ids(&buffer)[2], 101);
+        assert_eq!(buffer.len, capacity as usize);
+        assert_eq!(buffer.dropped(), 1);
+    }
+
+    #[test]
+    fn retain_replace_oldest() {
+        let mut buffer = ProjectileBuffer::new_retain::<Aged>(4)
+            .with_overflow_policy(OverflowPolicy::ReplaceOldest);
+        let capacity = buffer.capacity() as u32;
+        buffer.extend([Aged::new(0)]);
+        kill(&mut buffer, &[]);
+        buffer.extend((1..capacity + 1).map(Aged::new));
+        assert_eq!(ids(&buffer)[0], capacity);
+        assert_eq!(buffer.len, capacity as usize);
+        assert_eq!(buffer.dropped(), 1);
+    }
+
+    #[test]
+    fn retain_replace_expired() {
+        for policy in [
+            OverflowPolicy::ReplaceOldest,
+            OverflowPolicy::ReplaceExpiring,
+        ] {
+            let mut buffer = ProjectileBuffer::new_retain::<Aged>(4).with_overflow_policy(policy);
+            let capacity = buffer.capacity() as u32;
+            buffer.extend((0..capacity).map(Aged::new));
+            let item = &mut buffer.get_mut::<Aged>()[1];
+            item.age = 1.;
+            item.dead = true;
+            buffer.extend([Aged::new(100), Aged::new(101)]);
+            assert!(buffer.len <= buffer.capacity());
+            assert_eq!(buffer.get::<Aged>().len(), capacity as usize);
+            assert!(ids(&buffer).contains(&101));
+            assert_eq!(buffer.dropped(), 1);
+        }
+    }
+
+    #[test]
+    fn ring_full_extend() {
+        let mut buffer = ProjectileBuffer::new_ring::<Aged>(4);
+        let capacity = buffer.capacity() as u32;
+        buffer.extend((0..capacity * 3).map(Aged::new));
+        assert_eq!(ids(&buffer), (0..capacity).collect::<Vec<_>>());
+        assert_eq!(buffer.dropped(), capacity as usize * 2);
+
+        kill(&mut buffer, &[1, 3]);
+        buffer.extend((100..104).map(Aged::new));
+        assert_eq!(&ids(&buffer)[..4], &[0, 100, 2, 101]);
+        assert_eq!(buffer.len, capacity as usize);
+        assert_eq!(buffer.dropped(), capacity as usize * 2 + 2);
+    }
+
+    #[test]
+    fn growable_grows() {
+        let mut buffer = ProjectileBuffer::new_growable::<Aged>(4, None);
+        let initial = buffer.capacity();
+        buffer.extend((0..100).map(Aged::new));
+        assert_eq!(ids(&buffer), (0..100).collect::<Vec<_>>());
+        assert!(buffer.capacity() >= 100);
+        assert_eq!(buffer.dropped(), 0);
+
+        buffer.clear();
+        buffer.extend([Aged::new(0)]);
+        for _ in 0..SHRINK_AFTER_STEPS * 16 {
+            buffer.shrink_unused::<Aged>();
+        }
+        assert_eq!(buffer.capacity(), initial);
+        assert_eq!(ids(&buffer), [0]);
+    }
+
+    #[test]
+    fn growable_max() {
+        let mut buffer = ProjectileBuffer::new_growable::<Aged>(4, Some(40));
+        buffer.extend((0..100).map(Aged::new));
+        assert_eq!(buffer.capacity(), 40);
+        assert_eq!(ids(&buffer), (0..40).collect::<Vec<_>>());
+        assert_eq!(buffer.dropped(), 60);
+    }
+
+    #[test]
+    fn growable_tracking() {
+        let mut buffer = ProjectileBuffer::new_growable::<Aged>(4, None);
+        buffer.track_previous(true);
+        buffer.extend((0..4).map(Aged::new));
+        let handle = buffer.handle(2).unwrap();
+        buffer.extend((4..100).map(Aged::new));
+        assert_eq!(buffer.get_by_handle::<Aged>(handle).unwrap().id, 2);
+        assert_eq!(buffer.tracking.previous.as_ref().unwrap().len(), 100);
+        let last = buffer.handle(99).unwrap();
+        assert_eq!(buffer.get_by_handle::<Aged>(last).unwrap().id, 99);
+    }
+}
@@ -0,0 +1,560 @@
+use bevy::{
+    math::{Affine3A, Quat, Vec3},
+    prelude::{Component, Query, ResMut, Resource},
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::{
+    ExpirationState, Projectile, ProjectileBuffer, ProjectileEvent, ProjectileEventBuffer,
+    ProjectileEventType,
+};
+
+/// Distance particles are kept away from surfaces to avoid tunneling on the next step.
+pub(crate) const SKIN: f32 = 1e-4;
+
+/// Position and velocity of a projectile, required for collision.
+///
+/// Exposed via [`Projectile::as_physics_mut`].
+pub trait PhysicsProjectile {
+    /// Obtain the position of the projectile.
+    fn position(&self) -> Vec3;
+    /// Set the position of the projectile.
+    fn set_position(&mut self, position: Vec3);
+    /// Obtain the velocity of the projectile.
+    fn velocity(&self) -> Vec3;
+    /// Set the velocity of the projectile.
+    fn set_velocity(&mut self, velocity: Vec3);
+    /// Expire the projectile on contact, called with [`ExpirationState::Collide`]
+    /// by [`CollisionResponse::Kill`] and [`ProjectileHitDetection::expire`](crate::ProjectileHitDetection::expire).
+    ///
+    /// The projectile should return `state` in [`Projectile::expiration_state`] afterwards,
+    /// i.e. by storing it or setting its lifetime past the end.
+    fn expire(&mut self, state: ExpirationState);
+}
+
+/// A static shape projectiles collide with, uses the entity's [`GlobalTransform`].
+///
+/// Scale is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[require(Transform)]
+pub enum ProjectileCollider {
+    /// An infinite one sided plane facing local `+Y`.
+    Plane,
+    /// A sphere.
+    Sphere { radius: f32 },
+    /// An oriented box.
+    Cuboid { half_size: Vec3 },
+    /// A capsule along local `Y`.
+    Capsule { radius: f32, half_length: f32 },
+}
+
+/// What happens to a projectile when it hits a [`ProjectileCollider`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionResponse {
+    /// Reflect velocity, `restitution` scales the normal component
+    /// and `friction` reduces the tangential component.
+    Bounce { restitution: f32, friction: f32 },
+    /// Stop at the contact point.
+    Stick,
+    /// Remove the normal component of velocity, `friction` reduces the rest.
+    Slide { friction: f32 },
+    /// Expire with [`ExpirationState::Collide`], see [`PhysicsProjectile::expire`].
+    Kill,
+}
+
+impl Default for CollisionResponse {
+    fn default() -> Self {
+        CollisionResponse::Bounce {
+            restitution: 0.5,
+            friction: 0.,
+        }
+    }
+}
+
+/// Enables collision against [`ProjectileCollider`]s on a [`ProjectileCluster`](crate::ProjectileCluster).
+///
+/// Only projectiles that implement [`Projectile::as_physics_mut`] are affected.
+/// If [`ProjectileEventBuffer`] is present, every contact writes a
+/// [`ProjectileEventType::Collide`] event, including resting or sliding contacts.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Component)]
+pub struct ProjectileCollision {
+    /// Response to a collision.
+    pub response: CollisionResponse,
+    /// Radius of projectiles, colliders are inflated by this amount.
+    pub radius: f32,
+}
+
+impl ProjectileCollision {
+    /// Create a collision component with a response and zero radius.
+    pub const fn new(response: CollisionResponse) -> Self {
+        Self {
+            response,
+            radius: 0.,
+        }
+    }
+
+    /// Set the radius of projectiles.
+    pub const fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+}
+
+/// A [`ProjectileCollider`] in world space.
+#[derive(Debug, Clone, Copy)]
+pub struct WorldCollider {
+    pub collider: ProjectileCollider,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+/// All [`ProjectileCollider`]s in the world, collected at the start of [`ProjectileSet::Simulate`](crate::ProjectileSet::Simulate).
+#[derive(Debug, Default, Resource)]
+pub struct ProjectileColliders(pub Vec<WorldCollider>);
+
+pub(crate) fn collect_projectile_colliders(
+    mut colliders: ResMut<ProjectileColliders>,
+    query: Query<(&ProjectileCollider, &GlobalTransform)>,
+) {
+    colliders.0.clear();
+    colliders
+        .0
+        .extend(query.iter().map(|(collider, transform)| {
+            let (_, rotation, translation) = transform.to_scale_rotation_translation();
+            WorldCollider {
+                collider: *collider,
+                translation,
+                rotation,
+            }
+        }));
+}
+
+/// Contact of a swept particle against a collider.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Contact {
+    /// Fraction of the segment travelled.
+    pub fraction: f32,
+    pub normal: Vec3,
+}
+
+/// Returns the entry distance of a ray with a normalized direction against a sphere.
+fn ray_sphere(origin: Vec3, dir: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let oc = origin - center;
+    let b = oc.dot(dir);
+    let c = oc.length_squared() - radius * radius;
+    let h = b * b - c;
+    if c < 0. || h < 0. {
+        return None;
+    }
+    Some(-b - h.sqrt())
+}
+
+impl WorldCollider {
+    /// Sweep a sphere of `radius` from `from` to `to`, returns the earliest contact.
+    ///
+    /// Segments that start inside the collider are ignored.
+    pub(crate) fn sweep(&self, from: Vec3, to: Vec3, radius: f32) -> Option<Contact> {
+        let up = self.rotation * Vec3::Y;
+        match self.collider {
+            ProjectileCollider::Plane => {
+                let d0 = (from - self.translation).dot(up) - radius;
+                let d1 = (to - self.translation).dot(up) - radius;
+                if d0 < 0. || d1 >= 0. {
+                    return None;
+                }
+                Some(Contact {
+                    fraction: d0 / (d0 - d1),
+                    normal: up,
+                })
+            }
+            ProjectileCollider::Sphere { radius: r } => {
+                let len = from.distance(to);
+                if len <= 0. {
+                    return None;
+                }
+                let dir = (to - from) / len;
+                let t = ray_sphere(from, dir, self.translation, r + radius)?;
+                // The sphere is behind the segment.
+                if !(0. ..=len).contains(&t) {
+                    return None;
+                }
+                Some(Contact {
+                    fraction: t / len,
+                    normal: (from + dir * t - self.translation).normalize_or(up),
+                })
+            }
+            ProjectileCollider::Cuboid { half_size } => {
+                let inv = self.rotation.inverse();
+                let origin = inv * (from - self.translation);
+                let delta = inv * (to - from);
+                let half_size = half_size + radius;
+                let mut near = f32::MIN;
+                let mut far = f32::MAX;
+                let mut axis = 0;
+                for i in 0..3 {
+                    if delta[i].abs() < f32::EPSILON {
+                        if origin[i].abs() > half_size[i] {
+                            return None;
+                        }
+                        continue;
+                    }
+                    let t1 = (-half_size[i] - origin[i]) / delta[i];
+                    let t2 = (half_size[i] - origin[i]) / delta[i];
+                    let (t1, t2) = (t1.min(t2), t1.max(t2));
+                    if t1 > near {
+                        near = t1;
+                        axis = i;
+                    }
+                    far = far.min(t2);
+                }
+                if near > far || !(0.0..=1.).contains(&near) {
+                    return None;
+                }
+                let mut normal = Vec3::ZERO;
+                normal[axis] = -delta[axis].signum();
+                Some(Contact {
+                    fraction: near,
+                    normal: self.rotation * normal,
+                })
+            }
+            ProjectileCollider::Capsule {
+                radius: r,
+                half_length,
+            } => {
+                let len = from.distance(to);
+                if len <= 0. {
+                    return None;
+                }
+                let dir = (to - from) / len;
+                let r = r + radius;
+                let a = self.translation - up * half_length;
+                let b = self.translation + up * half_length;
+                let closest = |p: Vec3| a + up * (p - a).dot(up).clamp(0., half_length * 2.);
+                if from.distance_squared(closest(from)) < r * r {
+                    return None;
+                }
+                let mut t = [a, b]
+                    .into_iter()
+                    .filter_map(|c| ray_sphere(from, dir, c, r))
+                    .fold(f32::MAX, f32::min);
+                // Infinite cylinder around the axis.
+                let oa = from - a;
+                let dir_perp = dir - up * dir.dot(up);
+                let oa_perp = oa - up * oa.dot(up);
+                let qa = dir_perp.length_squared();
+                if qa > f32::EPSILON {
+                    let qb = dir_perp.dot(oa_perp);
+                    let qc = oa_perp.length_squared() - r * r;
+                    let h = qb * qb - qa * qc;
+                    if h >= 0. {
+                        let tc = (-qb - h.sqrt()) / qa;
+                        let y = (oa + dir * tc).dot(up);
+                        if tc >= 0. && (0.0..=half_length * 2.).contains(&y) {
+                            t = t.min(tc);
+                        }
+                    }
+                }
+                if !(0.0..=len).contains(&t) {
+                    return None;
+                }
+                let point = from + dir * t;
+                Some(Contact {
+                    fraction: t / len,
+                    normal: (point - closest(point)).normalize_or(up),
+                })
+            }
+        }
+    }
+}
+
+/// Test particles that moved in the last step against colliders and apply responses.
+///
+/// Requires transforms before the step to be tracked.
+pub(crate) fn collide_particles<P: Projectile>(
+    buffer: &mut ProjectileBuffer,
+    colliders: &[WorldCollider],
+    collision: &ProjectileCollision,
+    to_world: Affine3A,
+    mut events: Option<&mut ProjectileEventBuffer>,
+) {
+    if colliders.is_empty() {
+        return;
+    }
+    let to_local = to_world.inverse();
+    let (buf, tracking) = buffer.get_mut_tracked::<P>();
+    let Some(previous) = tracking.previous_mut(buf.len()) else {
+        return;
+    };
+    for (item, previous) in buf.iter_mut().zip(previous.iter()) {
+        if item.is_expired() {
+            continue;
+        }
+        let Some(physics) = item.as_physics_mut() else {
+            continue;
+        };
+        let from = to_world.transform_point3(previous.translation);
+        let to = to_world.transform_point3(physics.position());
+        let Some(contact) = colliders
+            .iter()
+            .filter_map(|c| c.sweep(from, to, collision.radius))
+            .min_by(|a, b| a.fraction.total_cmp(&b.fraction))
+        else {
+            continue;
+        };
+        let normal = contact.normal;
+        let point = from.lerp(to, contact.fraction) + normal * SKIN;
+        let velocity = to_world.transform_vector3(physics.velocity());
+        let rest = to - point;
+        let (position, velocity) = match collision.response {
+            CollisionResponse::Bounce {
+                restitution,
+                friction,
+            } => {
+                let reflect = |v: Vec3| {
+                    let vn = normal * v.dot(normal);
+                    (v - vn) * (1. - friction) - vn * restitution
+                };
+                (point + reflect(rest), reflect(velocity))
+            }
+            CollisionResponse::Stick => (point, Vec3::ZERO),
+            CollisionResponse::Slide { friction } => {
+                let slide = |v: Vec3| (v - normal * v.dot(normal)) * (1. - friction);
+                (point + slide(rest), slide(velocity))
+            }
+            CollisionResponse::Kill => {
+                physics.expire(ExpirationState::Collide);
+                (point, velocity)
+            }
+        };
+        physics.set_position(to_local.transform_point3(position));
+        physics.set_velocity(to_local.transform_vector3(velocity));
+        if let Some(events) = events.as_deref_mut() {
+            let surface = point - normal * (collision.radius + SKIN);
+            events.push(ProjectileEvent {
+                event: ProjectileEventType::Collide,
+                seed: item.get_seed(),
+                index: item.get_index(),
+                lifetime: item.get_lifetime(),
+                position: to_local.transform_point3(surface),
+                tangent: item.get_tangent(),
+                normal: to_local.transform_vector3(normal).normalize_or_zero(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::{
+        math::{Affine3A, Quat, Vec3},
+        transform::components::Transform,
+    };
+
+    use super::{
+        collide_particles, CollisionResponse, Contact, PhysicsProjectile, ProjectileCollider,
+        ProjectileCollision, WorldCollider, SKIN,
+    };
+    use crate::{ExpirationState, Projectile, ProjectileBuffer};
+
+    fn collider(collider: ProjectileCollider) -> WorldCollider {
+        WorldCollider {
+            collider,
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+        }
+    }
+
+    fn sphere(radius: f32) -> WorldCollider {
+        collider(ProjectileCollider::Sphere { radius })
+    }
+
+    fn assert_contact(contact: Option<Contact>, fraction: f32, normal: Vec3) {
+        let contact = contact.unwrap();
+        assert!((contact.fraction - fraction).abs() < 1e-5, "{contact:?}");
+        assert!(contact.normal.abs_diff_eq(normal, 1e-5), "{contact:?}");
+    }
+
+    #[test]
+    fn sphere_approaching() {
+        let contact = sphere(1.).sweep(Vec3::X * 3., Vec3::X, 0.).unwrap();
+        assert!((contact.fraction - 1.).abs() < 1e-5);
+        assert!(contact.normal.abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn sphere_receding() {
+        assert!(sphere(1.).sweep(Vec3::X * 2., Vec3::X * 3., 0.).is_none());
+        assert!(sphere(1.)
+            .sweep(Vec3::X * 1.5, Vec3::X * 3., 0.25)
+            .is_none());
+    }
+
+    #[test]
+    fn sphere_inside() {
+        assert!(sphere(1.).sweep(Vec3::ZERO, Vec3::X * 3., 0.).is_none());
+    }
+
+    #[test]
+    fn plane_sweep() {
+        let plane = collider(ProjectileCollider::Plane);
+        assert_contact(plane.sweep(Vec3::Y * 2., -Vec3::Y * 2., 0.), 0.5, Vec3::Y);
+        assert_contact(plane.sweep(Vec3::Y * 2., -Vec3::Y * 2., 1.), 0.25, Vec3::Y);
+        assert!(plane.sweep(Vec3::Y, Vec3::Y * 2., 0.).is_none());
+        assert!(plane.sweep(-Vec3::Y, -Vec3::Y * 2., 0.).is_none());
+    }
+
+    #[test]
+    fn cuboid_sweep() {
+        let cuboid = collider(ProjectileCollider::Cuboid {
+            half_size: Vec3::new(1., 2., 1.),
+        });
+        assert_contact(
+            cuboid.sweep(Vec3::X * 3., -Vec3::X * 3., 0.),
+            1. / 3.,
+            Vec3::X,
+        );
+        assert_contact(cuboid.sweep(Vec3::Y * 4., -Vec3::Y * 4., 0.), 0.25, Vec3::Y);
+        assert!(cuboid.sweep(Vec3::X * 2., Vec3::X * 3., 0.).is_none());
+        assert!(cuboid.sweep(Vec3::ZERO, Vec3::X * 3., 0.).is_none());
+        assert!(cuboid
+            .sweep(Vec3::new(3., 3., 0.), Vec3::new(-3., 3., 0.), 0.)
+            .is_none());
+
+        let rotated = WorldCollider {
+            rotation: Quat::from_rotation_z(FRAC_PI_2),
+            ..cuboid
+        };
+        assert_contact(
+            rotated.sweep(Vec3::Y * 3., -Vec3::Y * 3., 0.),
+            1. / 3.,
+            Vec3::Y,
+        );
+    }
+
+    #[test]
+    fn capsule_sweep() {
+        let capsule = collider(ProjectileCollider::Capsule {
+            radius: 1.,
+            half_length: 1.,
+        });
+        assert_contact(
+            capsule.sweep(Vec3::X * 3., -Vec3::X * 3., 0.),
+            1. / 3.,
+            Vec3::X,
+        );
+        assert_contact(
+            capsule.sweep(Vec3::Y * 4., -Vec3::Y * 4., 0.),
+            0.25,
+            Vec3::Y,
+        );
+        assert_contact(
+            capsule.sweep(Vec3::new(3., 1.5, 0.), Vec3::new(-3., 1.5, 0.), 0.),
+            (3. - 0.75f32.sqrt()) / 6.,
+            Vec3::new(0.75f32.sqrt(), 0.5, 0.),
+        );
+        assert!(capsule.sweep(Vec3::X * 2., Vec3::X * 3., 0.).is_none());
+        assert!(capsule.sweep(Vec3::ZERO, Vec3::X * 3., 0.).is_none());
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Ball {
+        position: Vec3,
+        velocity: Vec3,
+        expired: ExpirationState,
+    }
+
+    impl PhysicsProjectile for Ball {
+        fn position(&self) -> Vec3 {
+            self.position
+        }
+
+        fn set_position(&mut self, position: Vec3) {
+            self.position = position
+        }
+
+        fn velocity(&self) -> Vec3 {
+            self.velocity
+        }
+
+        fn set_velocity(&mut self, velocity: Vec3) {
+            self.velocity = velocity
+        }
+
+        fn expire(&mut self, state: ExpirationState) {
+            self.expired = state
+        }
+    }
+
+    impl Projectile for Ball {
+        fn get_transform(&self) -> Transform {
+            Transform::from_translation(self.position)
+        }
+
+        fn update(&mut self, _: f32) {}
+
+        fn expiration_state(&self) -> ExpirationState {
+            self.expired
+        }
+
+        fn as_physics_mut(&mut self) -> Option<&mut dyn PhysicsProjectile> {
+            Some(self)
+        }
+    }
+
+    /// Move a ball from `(0, 1, 0)` to `(1, -1, 0)` through a ground plane.
+    fn collide(response: CollisionResponse) -> Ball {
+        let mut buffer = ProjectileBuffer::new_retain::<Ball>(1);
+        buffer.track_previous(true);
+        buffer.extend([Ball {
+            position: Vec3::new(1., -1., 0.),
+            velocity: Vec3::new(1., -2., 0.),
+            expired: ExpirationState::None,
+        }]);
+        let (_, tracking) = buffer.get_mut_tracked::<Ball>();
+        tracking.previous_mut(1).unwrap()[0] = Transform::from_translation(Vec3::Y);
+        collide_particles::<Ball>(
+            &mut buffer,
+            &[collider(ProjectileCollider::Plane)],
+            &ProjectileCollision::new(response),
+            Affine3A::IDENTITY,
+            None,
+        );
+        buffer.get::<Ball>()[0]
+    }
+
+    #[test]
+    fn bounce_response() {
+        let ball = collide(CollisionResponse::Bounce {
+            restitution: 1.,
+            friction: 0.,
+        });
+        assert!(ball
+            .position
+            .abs_diff_eq(Vec3::new(1., 1. + 2. * SKIN, 0.), 1e-5));
+        assert!(ball.velocity.abs_diff_eq(Vec3::new(1., 2., 0.), 1e-5));
+        assert_eq!(ball.expired, ExpirationState::None);
+    }
+
+    #[test]
+    fn stick_response() {
+        let ball = collide(CollisionResponse::Stick);
+        assert!(ball.position.abs_diff_eq(Vec3::new(0.5, SKIN, 0.), 1e-5));
+        assert_eq!(ball.velocity, Vec3::ZERO);
+    }
+
+    #[test]
+    fn slide_response() {
+        let ball = collide(CollisionResponse::Slide { friction: 0. });
+        assert!(ball.position.abs_diff_eq(Vec3::new(1., SKIN, 0.), 1e-5));
+        assert!(ball.velocity.abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn kill_response() {
+        let ball = collide(CollisionResponse::Kill);
+        assert!(ball.position.abs_diff_eq(Vec3::new(0.5, SKIN, 0.), 1e-5));
+        assert_eq!(ball.expired, ExpirationState::Collide);
+    }
+}
@@ -0,0 +1,829 @@
+//! Projectiles with attributes decided at runtime.
+use std::{fmt::Display, sync::Arc};
+
+use bevy::{
+    color::{ColorToComponents, Srgba},
+    math::{Vec2, Vec3, Vec4},
+    transform::components::Transform,
+};
+
+use crate::{
+    util::spawn_rate, ExpirationState, ParticleSeed, PhysicsProjectile, Projectile,
+    ProjectileInstanceBuffer, ProjectileSystem,
+};
+
+/// Format of an [`Attribute`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeFormat {
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+}
+
+impl AttributeFormat {
+    /// Number of `f32`s in the format.
+    pub const fn components(self) -> usize {
+        match self {
+            AttributeFormat::Float => 1,
+            AttributeFormat::Vec2 => 2,
+            AttributeFormat::Vec3 => 3,
+            AttributeFormat::Vec4 => 4,
+        }
+    }
+}
+
+/// A value that can be stored in an [`Attribute`].
+pub trait AttributeValue: Copy {
+    const FORMAT: AttributeFormat;
+
+    /// Read from `FORMAT.components()` floats.
+    fn read(data: &[f32]) -> Self;
+
+    /// Write to `FORMAT.components()` floats.
+    fn write(self, data: &mut [f32]);
+}
+
+impl AttributeValue for f32 {
+    const FORMAT: AttributeFormat = AttributeFormat::Float;
+
+    fn read(data: &[f32]) -> Self {
+        data[0]
+    }
+
+    fn write(self, data: &mut [f32]) {
+        data[0] = self
+    }
+}
+
+impl AttributeValue for Vec2 {
+    const FORMAT: AttributeFormat = AttributeFormat::Vec2;
+
+    fn read(data: &[f32]) -> Self {
+        Vec2::from_slice(data)
+    }
+
+    fn write(self, data: &mut [f32]) {
+        self.write_to_slice(data)
+    }
+}
+
+impl AttributeValue for Vec3 {
+    const FORMAT: AttributeFormat = AttributeFormat::Vec3;
+
+    fn read(data: &[f32]) -> Self {
+        Vec3::from_slice(data)
+    }
+
+    fn write(self, data: &mut [f32]) {
+        self.write_to_slice(data)
+    }
+}
+
+impl AttributeValue for Vec4 {
+    const FORMAT: AttributeFormat = AttributeFormat::Vec4;
+
+    fn read(data: &[f32]) -> Self {
+        Vec4::from_slice(data)
+    }
+
+    fn write(self, data: &mut [f32]) {
+        self.write_to_slice(data)
+    }
+}
+
+/// A named attribute of a [`ProjectileSchema`].
+///
+/// Attributes with the following names and formats are understood by [`DynamicProjectile`]:
+///
+/// * `position`: [`Vec3`], moved by `velocity`.
+/// * `velocity`: [`Vec3`].
+/// * `age`: [`f32`], advanced by time.
+/// * `lifetime`: [`f32`], the particle fades out when `age` reaches `lifetime`.
+/// * `size`: [`f32`], uniform scale of the transform.
+/// * `color`: [`Vec4`], in srgba.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attribute {
+    pub name: String,
+    pub format: AttributeFormat,
+    /// Initial value of the attribute, only the first `format.components()` values are used.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub default: [f32; 4],
+}
+
+impl Attribute {
+    /// Create an attribute with default value `0`.
+    pub fn new(name: impl Into<String>, format: AttributeFormat) -> Self {
+        Self {
+            name: name.into(),
+            format,
+            default: [0.; 4],
+        }
+    }
+
+    /// Set the initial value of the attribute.
+    pub fn with_default<T: AttributeValue>(mut self, value: T) -> Self {
+        self.format = T::FORMAT;
+        value.write(&mut self.default[..T::FORMAT.components()]);
+        self
+    }
+}
+
+/// Identifies an attribute of a [`ProjectileSchema`], obtained via [`ProjectileSchema::id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AttributeId {
+    offset: u8,
+    format: AttributeFormat,
+}
+
+impl AttributeId {
+    /// Offset of the attribute in number of `f32`s.
+    pub const fn offset(&self) -> usize {
+        self.offset as usize
+    }
+
+    /// Format of the attribute.
+    pub const fn format(&self) -> AttributeFormat {
+        self.format
+    }
+
+    const fn range(&self) -> std::ops::Range<usize> {
+        self.offset()..self.offset() + self.format.components()
+    }
+}
+
+/// Offsets of well known attributes, [`Builtins::NONE`] if absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Builtins {
+    position: u8,
+    velocity: u8,
+    age: u8,
+    lifetime: u8,
+    size: u8,
+    color: u8,
+}
+
+impl Builtins {
+    const NONE: u8 = u8::MAX;
+
+    const EMPTY: Self = Builtins {
+        position: Self::NONE,
+        velocity: Self::NONE,
+        age: Self::NONE,
+        lifetime: Self::NONE,
+        size: Self::NONE,
+        color: Self::NONE,
+    };
+
+    /// Obtain the slot of a well known attribute and its expected format.
+    fn slot(&mut self, name: &str) -> Option<(&mut u8, AttributeFormat)> {
+        Some(match name {
+            "position" => (&mut self.position, AttributeFormat::Vec3),
+            "velocity" => (&mut self.velocity, AttributeFormat::Vec3),
+            "age" => (&mut self.age, AttributeFormat::Float),
+            "lifetime" => (&mut self.lifetime, AttributeFormat::Float),
+            "size" => (&mut self.size, AttributeFormat::Float),
+            "color" => (&mut self.color, AttributeFormat::Vec4),
+            _ => return None,
+        })
+    }
+}
+
+/// Error creating a [`ProjectileSchema`] or a [`DynamicProjectileSystem`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// Two attributes have the same name.
+    Duplicate(String),
+    /// A well known attribute has the wrong format.
+    Format {
+        name: String,
+        expected: AttributeFormat,
+    },
+    /// Attributes do not fit in a [`ProjectileSchema`] or in `N` floats of a [`DynamicProjectile<N>`].
+    TooLarge { len: usize, max: usize },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Duplicate(name) => write!(f, "Duplicate attribute {name}."),
+            SchemaError::Format { name, expected } => {
+                write!(f, "Attribute {name} must be {expected:?}.")
+            }
+            SchemaError::TooLarge { len, max } => {
+                write!(
+                    f,
+                    "Attributes need {len} floats, at most {max} are available."
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Describes named attributes of [`DynamicProjectile`]s and their offsets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectileSchema {
+    attributes: Vec<(Attribute, AttributeId)>,
+    builtins: Builtins,
+    len: usize,
+}
+
+impl ProjectileSchema {
+    /// Create a schema, attributes are packed in order.
+    pub fn new(attributes: impl IntoIterator<Item = Attribute>) -> Result<Self, SchemaError> {
+        let mut result = ProjectileSchema {
+            attributes: Vec::new(),
+            builtins: Builtins::EMPTY,
+            len: 0,
+        };
+        for attribute in attributes {
+            if result.id(&attribute.name).is_some() {
+                return Err(SchemaError::Duplicate(attribute.name));
+            }
+            let len = result.len + attribute.format.components();
+            if len >= Builtins::NONE as usize {
+                return Err(SchemaError::TooLarge {
+                    len,
+                    max: Builtins::NONE as usize - 1,
+                });
+            }
+            let offset = result.len as u8;
+            if let Some((slot, expected)) = result.builtins.slot(&attribute.name) {
+                if attribute.format != expected {
+                    return Err(SchemaError::Format {
+                        name: attribute.name,
+                        expected,
+                    });
+                }
+                *slot = offset;
+            }
+            let id = AttributeId {
+                offset,
+                format: attribute.format,
+            };
+            result.attributes.push((attribute, id));
+            result.len = len;
+        }
+        Ok(result)
+    }
+
+    /// Obtain the id of an attribute by name.
+    pub fn id(&self, name: &str) -> Option<AttributeId> {
+        self.attributes
+            .iter()
+            .find(|(x, _)| x.name == name)
+            .map(|(_, id)| *id)
+    }
+
+    /// Iterate through attributes and their ids.
+    pub fn attributes(&self) -> impl Iterator<Item = (&Attribute, AttributeId)> {
+        self.attributes.iter().map(|(x, id)| (x, *id))
+    }
+
+    /// Number of `f32`s used by attributes.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no attributes.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Create a particle with default values.
+    ///
+    /// # Panics
+    ///
+    /// If the schema does not fit in `N` floats.
+    pub fn instantiate<const N: usize>(&self, seed: ParticleSeed) -> DynamicProjectile<N> {
+        if self.len > N {
+            panic!(
+                "Schema of {} floats does not fit in DynamicProjectile<{N}>.",
+                self.len
+            )
+        }
+        let mut data = [0.; N];
+        for (attribute, id) in &self.attributes {
+            data[id.range()].copy_from_slice(&attribute.default[..attribute.format.components()]);
+        }
+        DynamicProjectile {
+            seed,
+            expired: ExpirationState::None,
+            builtins: self.builtins,
+            data,
+        }
+    }
+}
+
+/// A [`Projectile`] with attributes described by a [`ProjectileSchema`],
+/// stores up to `N` floats.
+///
+/// Attributes are accessed by [`AttributeId`],
+/// well known attributes listed in [`Attribute`] drive the projectile's behavior.
+///
+/// # Capacity
+///
+/// Storage is inline and fixed at compile time, so the schema size is limited by `N`,
+/// which defaults to `16` floats, i.e. four `Vec4`s.
+/// [`DynamicProjectileSystem::new`] returns [`SchemaError::TooLarge`] if a schema does not fit,
+/// choose a larger `N` for such schemas, i.e. `DynamicProjectileSystem::<32>::new`.
+/// Every particle occupies `N` floats regardless of the schema.
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicProjectile<const N: usize = 16> {
+    pub seed: ParticleSeed,
+    pub expired: ExpirationState,
+    builtins: Builtins,
+    data: [f32; N],
+}
+
+impl<const N: usize> DynamicProjectile<N> {
+    /// Obtain the value of an attribute.
+    ///
+    /// # Panics
+    ///
+    /// If format mismatch or the id is from a larger schema.
+    pub fn get<T: AttributeValue>(&self, id: AttributeId) -> T {
+        if id.format != T::FORMAT {
+            panic!("Attribute format mismatch!")
+        }
+        T::read(&self.data[id.range()])
+    }
+
+    /// Set the value of an attribute.
+    ///
+    /// # Panics
+    ///
+    /// If format mismatch or the id is from a larger schema.
+    pub fn set<T: AttributeValue>(&mut self, id: AttributeId, value: T) {
+        if id.format != T::FORMAT {
+            panic!("Attribute format mismatch!")
+        }
+        value.write(&mut self.data[id.range()])
+    }
+
+    /// Obtain the raw attribute data.
+    pub fn data(&self) -> &[f32; N] {
+        &self.data
+    }
+
+    /// Obtain the mutable raw attribute data.
+    pub fn data_mut(&mut self) -> &mut [f32; N] {
+        &mut self.data
+    }
+
+    fn builtin<T: AttributeValue>(&self, offset: u8, default: T) -> T {
+        if offset == Builtins::NONE {
+            return default;
+        }
+        let offset = offset as usize;
+        T::read(&self.data[offset..offset + T::FORMAT.components()])
+    }
+
+    fn set_builtin<T: AttributeValue>(&mut self, offset: u8, value: T) {
+        if offset == Builtins::NONE {
+            return;
+        }
+        let offset = offset as usize;
+        value.write(&mut self.data[offset..offset + T::FORMAT.components()])
+    }
+
+    /// Obtain `position`, `0` if absent.
+    pub fn position(&self) -> Vec3 {
+        self.builtin(self.builtins.position, Vec3::ZERO)
+    }
+
+    /// Obtain `velocity`, `0` if absent.
+    pub fn velocity(&self) -> Vec3 {
+        self.builtin(self.builtins.velocity, Vec3::ZERO)
+    }
+
+    /// Obtain `age`, `0` if absent.
+    pub fn age(&self) -> f32 {
+        self.builtin(self.builtins.age, 0.)
+    }
+
+    /// Obtain `lifetime`, infinite if absent.
+    pub fn lifetime(&self) -> f32 {
+        self.builtin(self.builtins.lifetime, f32::INFINITY)
+    }
+
+    /// Obtain `size`, `1` if absent.
+    pub fn size(&self) -> f32 {
+        self.builtin(self.builtins.size, 1.)
+    }
+
+    /// Obtain `color`, white if absent.
+    pub fn color(&self) -> Vec4 {
+        self.builtin(self.builtins.color, Vec4::ONE)
+    }
+}
+
+impl<const N: usize> PhysicsProjectile for DynamicProjectile<N> {
+    fn position(&self) -> Vec3 {
+        DynamicProjectile::position(self)
+    }
+
+    fn set_position(&mut self, position: Vec3) {
+        self.set_builtin(self.builtins.position, position)
+    }
+
+    fn velocity(&self) -> Vec3 {
+        DynamicProjectile::velocity(self)
+    }
+
+    fn set_velocity(&mut self, velocity: Vec3) {
+        self.set_builtin(self.builtins.velocity, velocity)
+    }
+
+    fn expire(&mut self, state: ExpirationState) {
+        self.expired = state;
+    }
+}
+
+impl<const N: usize> Projectile for DynamicProjectile<N> {
+    fn get_seed(&self) -> ParticleSeed {
+        self.seed
+    }
+
+    fn get_lifetime(&self) -> f32 {
+        self.age()
+    }
+
+    fn get_fac(&self) -> f32 {
+        if self.builtins.lifetime == Builtins::NONE {
+            self.age()
+        } else {
+            self.age() / self.lifetime()
+        }
+    }
+
+    fn get_transform(&self) -> Transform {
+        Transform::from_translation(self.position()).with_scale(Vec3::splat(self.size()))
+    }
+
+    fn get_color(&self) -> Srgba {
+        Srgba::from_vec4(self.color())
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.set_builtin(self.builtins.age, self.age() + dt);
+        self.set_builtin(
+            self.builtins.position,
+            self.position() + self.velocity() * dt,
+        );
+    }
+
+    fn expiration_state(&self) -> ExpirationState {
+        if self.expired.is_expired() {
+            return self.expired;
+        }
+        ExpirationState::fizzle_if(self.age() >= self.lifetime())
+    }
+
+    fn as_physics_mut(&mut self) -> Option<&mut dyn PhysicsProjectile> {
+        if self.builtins.position == Builtins::NONE || self.builtins.velocity == Builtins::NONE {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+/// Data written to a field of an [`InstanceLayout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstanceSource {
+    /// Value of an attribute.
+    Attribute(AttributeId),
+    /// [`ParticleSeed::as_f32`] as a `f32`.
+    Seed,
+    /// [`Projectile::get_lifetime`] as a `f32`.
+    Lifetime,
+    /// [`Projectile::get_fac`] as a `f32`.
+    Fac,
+    /// The first 3 rows of the transform matrix as 3 [`Vec4`]s,
+    /// same as [`DefaultInstanceBuffer`](crate::DefaultInstanceBuffer).
+    Transform,
+    /// A constant `f32`.
+    Constant(f32),
+}
+
+impl InstanceSource {
+    /// Size of the written data in bytes.
+    pub const fn size(&self) -> usize {
+        match self {
+            InstanceSource::Attribute(id) => id.format.components() * 4,
+            InstanceSource::Transform => 48,
+            _ => 4,
+        }
+    }
+}
+
+/// Maps attributes of [`DynamicProjectile`]s to the instance buffer of an
+/// [`InstancedMaterial`](crate::InstancedMaterial).
+///
+/// Bytes not covered by a field are zeroed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstanceLayout {
+    /// Size of an instance in bytes.
+    pub stride: usize,
+    /// Byte offsets and sources of fields.
+    pub fields: Vec<(usize, InstanceSource)>,
+}
+
+impl InstanceLayout {
+    /// Create an empty layout the size of an instance buffer.
+    pub fn new<B: ProjectileInstanceBuffer>() -> Self {
+        Self {
+            stride: size_of::<B>(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Write a source at a byte offset, usually the offset of a
+    /// [`VertexAttribute`](bevy::render::render_resource::VertexAttribute) of the instance buffer.
+    ///
+    /// # Panics
+    ///
+    /// If the field is out of bounds.
+    pub fn with_field(mut self, offset: usize, source: InstanceSource) -> Self {
+        if offset + source.size() > self.stride {
+            panic!(
+                "Field at {offset} does not fit in an instance of {} bytes.",
+                self.stride
+            )
+        }
+        self.fields.push((offset, source));
+        self
+    }
+
+    /// Write a particle to `bytes`.
+    pub fn write<const N: usize>(
+        &self,
+        particle: &DynamicProjectile<N>,
+        transform: Transform,
+        bytes: &mut Vec<u8>,
+    ) {
+        let start = bytes.len();
+        bytes.resize(start + self.stride, 0);
+        let instance = &mut bytes[start..];
+        for (offset, source) in &self.fields {
+            let mut write = |values: &[f32]| {
+                let values: &[u8] = bytemuck::cast_slice(values);
+                instance[*offset..*offset + values.len()].copy_from_slice(values);
+            };
+            match source {
+                InstanceSource::Attribute(id) => write(&particle.data[id.range()]),
+                InstanceSource::Seed => write(&[particle.seed.as_f32()]),
+                InstanceSource::Lifetime => write(&[particle.get_lifetime()]),
+                InstanceSource::Fac => write(&[particle.get_fac()]),
+                InstanceSource::Transform => {
+                    let matrix = transform.compute_matrix();
+                    write(
+                        &[matrix.row(0), matrix.row(1), matrix.row(2)]
+                            .map(|x| x.to_array())
+                            .concat(),
+                    )
+                }
+                InstanceSource::Constant(value) => write(&[*value]),
+            }
+        }
+    }
+}
+
+/// A [`ProjectileSystem`] of [`DynamicProjectile`]s.
+///
+/// Particles are spawned at `rate` with default values of the schema,
+/// then modified by `init`.
+#[derive(Debug, Clone)]
+pub struct DynamicProjectileSystem<const N: usize = 16> {
+    /// Attributes of spawned particles.
+    pub schema: Arc<ProjectileSchema>,
+    /// Nominal capacity of the particle buffer.
+    pub capacity: usize,
+    /// Particles spawned per second.
+    pub rate: f32,
+    /// Initialize a spawned particle, i.e. randomize attributes with its seed.
+    pub init: Option<fn(&ProjectileSchema, &mut DynamicProjectile<N>)>,
+    /// Extract to a custom instance buffer,
+    /// uses [`DefaultInstanceBuffer`](crate::DefaultInstanceBuffer) if `None`.
+    pub instance_layout: Option<InstanceLayout>,
+    spawn_meta: f32,
+}
+
+impl<const N: usize> DynamicProjectileSystem<N> {
+    /// Create a system, fails if the schema does not fit in `N` floats.
+    pub fn new(
+        schema: impl Into<Arc<ProjectileSchema>>,
+        capacity: usize,
+    ) -> Result<Self, SchemaError> {
+        let schema = schema.into();
+        if schema.len() > N {
+            return Err(SchemaError::TooLarge {
+                len: schema.len(),
+                max: N,
+            });
+        }
+        Ok(Self {
+            schema,
+            capacity,
+            rate: 0.,
+            init: None,
+            instance_layout: None,
+            spawn_meta: 0.,
+        })
+    }
+
+    /// Set the spawn rate.
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Set the function that initializes spawned particles.
+    pub fn with_init(mut self, init: fn(&ProjectileSchema, &mut DynamicProjectile<N>)) -> Self {
+        self.init = Some(init);
+        self
+    }
+
+    /// Set the layout of extracted instances.
+    pub fn with_instance_layout(mut self, layout: InstanceLayout) -> Self {
+        self.instance_layout = Some(layout);
+        self
+    }
+}
+
+impl<const N: usize> ProjectileSystem for DynamicProjectileSystem<N> {
+    type Projectile = DynamicProjectile<N>;
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn spawn_step(&mut self, dt: f32) -> usize {
+        spawn_rate(&mut self.spawn_meta, self.rate, dt)
+    }
+
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile {
+        let mut particle = self.schema.instantiate(seed);
+        if let Some(init) = self.init {
+            init(&self.schema, &mut particle);
+        }
+        particle
+    }
+
+    fn extract_particle(
+        &self,
+        particle: &Self::Projectile,
+        transform: Option<Transform>,
+        bytes: &mut Vec<u8>,
+    ) {
+        match &self.instance_layout {
+            Some(layout) => layout.write(
+                particle,
+                transform.unwrap_or(particle.get_transform()),
+                bytes,
+            ),
+            None => match transform {
+                Some(transform) => bytes.extend(bytemuck::bytes_of(
+                    &particle.extract_interpolated(transform),
+                )),
+                None => bytes.extend(bytemuck::bytes_of(&particle.extract())),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        math::{Vec2, Vec3, Vec4},
+        transform::components::Transform,
+    };
+
+    use super::{
+        Attribute, AttributeFormat, DynamicProjectileSystem, InstanceLayout, InstanceSource,
+        ProjectileSchema, SchemaError,
+    };
+    use crate::{DefaultInstanceBuffer, ParticleSeed, Projectile};
+
+    fn schema() -> ProjectileSchema {
+        ProjectileSchema::new([
+            Attribute::new("age", AttributeFormat::Float),
+            Attribute::new("custom", AttributeFormat::Vec2).with_default(Vec2::new(1., 2.)),
+            Attribute::new("position", AttributeFormat::Vec3),
+            Attribute::new("velocity", AttributeFormat::Vec3).with_default(Vec3::X),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn schema_layout() {
+        let schema = schema();
+        assert_eq!(schema.len(), 9);
+        let offsets: Vec<_> = schema
+            .attributes()
+            .map(|(x, id)| (x.name.as_str(), id.offset(), id.format()))
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                ("age", 0, AttributeFormat::Float),
+                ("custom", 1, AttributeFormat::Vec2),
+                ("position", 3, AttributeFormat::Vec3),
+                ("velocity", 6, AttributeFormat::Vec3),
+            ]
+        );
+        assert!(schema.id("missing").is_none());
+        let particle = schema.instantiate::<16>(ParticleSeed(0));
+        assert_eq!(particle.data()[..9], [0., 1., 2., 0., 0., 0., 1., 0., 0.]);
+        assert_eq!(particle.lifetime(), f32::INFINITY);
+        assert_eq!(particle.size(), 1.);
+    }
+
+    #[test]
+    fn schema_errors() {
+        let float = |name: &str| Attribute::new(name, AttributeFormat::Float);
+        assert_eq!(
+            ProjectileSchema::new([float("a"), float("a")]),
+            Err(SchemaError::Duplicate("a".into()))
+        );
+        assert_eq!(
+            ProjectileSchema::new([float("position")]),
+            Err(SchemaError::Format {
+                name: "position".into(),
+                expected: AttributeFormat::Vec3
+            })
+        );
+        let many = (0..64).map(|i| Attribute::new(i.to_string(), AttributeFormat::Vec4));
+        assert_eq!(
+            ProjectileSchema::new(many),
+            Err(SchemaError::TooLarge { len: 256, max: 254 })
+        );
+        assert_eq!(
+            DynamicProjectileSystem::<8>::new(schema(), 16).err(),
+            Some(SchemaError::TooLarge { len: 9, max: 8 })
+        );
+    }
+
+    #[test]
+    fn attribute_get_set() {
+        let schema = schema();
+        let custom = schema.id("custom").unwrap();
+        let position = schema.id("position").unwrap();
+        let mut particle = schema.instantiate::<16>(ParticleSeed(0));
+        assert_eq!(particle.get::<Vec2>(custom), Vec2::new(1., 2.));
+        particle.set(custom, Vec2::new(3., 4.));
+        particle.set(position, Vec3::Y);
+        assert_eq!(particle.get::<Vec2>(custom), Vec2::new(3., 4.));
+        assert_eq!(particle.position(), Vec3::Y);
+        particle.update(0.5);
+        assert_eq!(particle.position(), Vec3::new(0.5, 1., 0.));
+        assert_eq!(particle.age(), 0.5);
+        assert_eq!(particle.get::<Vec2>(custom), Vec2::new(3., 4.));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attribute format mismatch!")]
+    fn attribute_format_mismatch() {
+        let schema = schema();
+        let custom = schema.id("custom").unwrap();
+        schema
+            .instantiate::<16>(ParticleSeed(0))
+            .get::<Vec4>(custom);
+    }
+
+    #[test]
+    fn instance_layout() {
+        let schema = schema();
+        let custom = schema.id("custom").unwrap();
+        let layout = InstanceLayout::new::<DefaultInstanceBuffer>()
+            .with_field(0, InstanceSource::Transform)
+            .with_field(48, InstanceSource::Attribute(custom))
+            .with_field(60, InstanceSource::Constant(5.));
+        let mut bytes = Vec::new();
+        layout.write(
+            &schema.instantiate::<16>(ParticleSeed(0)),
+            Transform::from_xyz(7., 8., 9.),
+            &mut bytes,
+        );
+        assert_eq!(bytes.len(), layout.stride);
+        let floats: &[f32] = bytemuck::cast_slice(&bytes);
+        assert_eq!([floats[3], floats[7], floats[11]], [7., 8., 9.]);
+        assert_eq!(floats[12..16], [1., 2., 0., 5.]);
+        assert!(floats[16..].iter().all(|x| *x == 0.));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn instance_layout_out_of_bounds() {
+        let schema = schema();
+        let custom = schema.id("custom").unwrap();
+        let stride = size_of::<DefaultInstanceBuffer>();
+        let _ = InstanceLayout::new::<DefaultInstanceBuffer>()
+            .with_field(stride - 4, InstanceSource::Attribute(custom));
+    }
+}
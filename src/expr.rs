@@ -0,0 +1,591 @@
+//! A small expression language for particle parameters.
+//!
+//! Expressions are compiled to bytecode once and evaluated against [`ExprVars`].
+//!
+//! ```
+//! # use berdicles::expr::{Expr, ExprVars};
+//! let expr = Expr::compile("3 + sin(age * 4) * seed").unwrap();
+//! assert_eq!(expr.eval(&ExprVars::default()), 3.);
+//! ```
+//!
+//! # Syntax
+//!
+//! * Numbers: `1`, `0.5`, `1e-3`.
+//! * Operators: `+`, `-`, `*`, `/`, `%` and `^` for power, with parentheses.
+//! * Variables: `age`, `fac`, `seed`, `index`, `time`,
+//!   `position.x`, `position.y`, `position.z`, `velocity.x`, `velocity.y`, `velocity.z`.
+//! * Constants: `pi`, `tau`, `e`.
+//! * Functions: `sin`, `cos`, `tan`, `asin`, `acos`, `atan`, `atan2`,
+//!   `abs`, `sign`, `sqrt`, `exp`, `ln`, `floor`, `ceil`, `round`, `fract`,
+//!   `min`, `max`, `pow`, `step`, `clamp`, `lerp`, `smoothstep`.
+use std::{fmt::Display, str::FromStr};
+
+use bevy::math::Vec3;
+
+/// Maximum depth of the evaluation stack.
+const MAX_STACK: usize = 32;
+/// Maximum nesting of parentheses, function calls and unary operators.
+const MAX_NESTING: usize = 64;
+
+/// Variables available to an [`Expr`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExprVars {
+    /// Time since the particle spawned.
+    pub age: f32,
+    /// Normalized lifetime of the particle.
+    pub fac: f32,
+    /// Seed of the particle in `0.0..1.0`.
+    pub seed: f32,
+    /// Index of the particle.
+    pub index: u32,
+    /// Time of the emitter.
+    pub time: f32,
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Var {
+    Age,
+    Fac,
+    Seed,
+    Index,
+    Time,
+    Position(usize),
+    Velocity(usize),
+}
+
+impl Var {
+    fn parse(name: &str) -> Option<Self> {
+        let axis = |x: &str| match x {
+            "x" => Some(0),
+            "y" => Some(1),
+            "z" => Some(2),
+            _ => None,
+        };
+        Some(match name.split_once('.') {
+            None => match name {
+                "age" => Var::Age,
+                "fac" => Var::Fac,
+                "seed" => Var::Seed,
+                "index" => Var::Index,
+                "time" => Var::Time,
+                _ => return None,
+            },
+            Some(("position", x)) => Var::Position(axis(x)?),
+            Some(("velocity", x)) => Var::Velocity(axis(x)?),
+            _ => return None,
+        })
+    }
+
+    fn get(self, vars: &ExprVars) -> f32 {
+        match self {
+            Var::Age => vars.age,
+            Var::Fac => vars.fac,
+            Var::Seed => vars.seed,
+            Var::Index => vars.index as f32,
+            Var::Time => vars.time,
+            Var::Position(axis) => vars.position[axis],
+            Var::Velocity(axis) => vars.velocity[axis],
+        }
+    }
+}
+
+/// An instruction of a compiled [`Expr`].
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Const(f32),
+    Var(Var),
+    Unary(fn(f32) -> f32),
+    Binary(fn(f32, f32) -> f32),
+    Ternary(fn(f32, f32, f32) -> f32),
+}
+
+impl Op {
+    /// Number of values popped from the stack.
+    const fn arity(&self) -> usize {
+        match self {
+            Op::Const(_) | Op::Var(_) => 0,
+            Op::Unary(_) => 1,
+            Op::Binary(_) => 2,
+            Op::Ternary(_) => 3,
+        }
+    }
+}
+
+fn constant(name: &str) -> Option<f32> {
+    Some(match name {
+        "pi" => std::f32::consts::PI,
+        "tau" => std::f32::consts::TAU,
+        "e" => std::f32::consts::E,
+        _ => return None,
+    })
+}
+
+fn function(name: &str) -> Option<Op> {
+    Some(match name {
+        "sin" => Op::Unary(f32::sin),
+        "cos" => Op::Unary(f32::cos),
+        "tan" => Op::Unary(f32::tan),
+        "asin" => Op::Unary(f32::asin),
+        "acos" => Op::Unary(f32::acos),
+        "atan" => Op::Unary(f32::atan),
+        "abs" => Op::Unary(f32::abs),
+        "sign" => Op::Unary(f32::signum),
+        "sqrt" => Op::Unary(f32::sqrt),
+        "exp" => Op::Unary(f32::exp),
+        "ln" => Op::Unary(f32::ln),
+        "floor" => Op::Unary(f32::floor),
+        "ceil" => Op::Unary(f32::ceil),
+        "round" => Op::Unary(f32::round),
+        "fract" => Op::Unary(|x| x - x.floor()),
+        "atan2" => Op::Binary(f32::atan2),
+        "min" => Op::Binary(f32::min),
+        "max" => Op::Binary(f32::max),
+        "pow" => Op::Binary(f32::powf),
+        "step" => Op::Binary(|edge, x| if x < edge { 0. } else { 1. }),
+        "clamp" => Op::Ternary(|x, min, max| x.max(min).min(max)),
+        "lerp" => Op::Ternary(|a, b, t| a + (b - a) * t),
+        "smoothstep" => Op::Ternary(|a, b, x| {
+            let t = ((x - a) / (b - a)).clamp(0., 1.);
+            t * t * (3. - 2. * t)
+        }),
+        _ => return None,
+    })
+}
+
+/// Error compiling an [`Expr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    /// Byte offset of the error in the source.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for ExprError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}.", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'t> {
+    Number(f32),
+    Ident(&'t str),
+    Symbol(u8),
+    End,
+}
+
+/// Recursive descent parser emitting bytecode.
+struct Parser<'t> {
+    source: &'t str,
+    position: usize,
+    token: Token<'t>,
+    token_position: usize,
+    ops: Vec<Op>,
+    /// Depth of the evaluation stack.
+    depth: usize,
+    /// Recursion depth of the parser.
+    nesting: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ExprError> {
+        Err(ExprError {
+            position: self.token_position,
+            message: message.into(),
+        })
+    }
+
+    fn advance(&mut self) -> Result<(), ExprError> {
+        let bytes = self.source.as_bytes();
+        while bytes
+            .get(self.position)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+        self.token_position = self.position;
+        let start = self.position;
+        let Some(&c) = bytes.get(start) else {
+            self.token = Token::End;
+            return Ok(());
+        };
+        if c.is_ascii_digit() || c == b'.' {
+            while bytes
+                .get(self.position)
+                .is_some_and(|c| c.is_ascii_digit() || *c == b'.')
+            {
+                self.position += 1;
+            }
+            if matches!(bytes.get(self.position), Some(b'e' | b'E')) {
+                let mut end = self.position + 1;
+                if matches!(bytes.get(end), Some(b'+' | b'-')) {
+                    end += 1;
+                }
+                if bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                    self.position = end;
+                    while bytes.get(self.position).is_some_and(u8::is_ascii_digit) {
+                        self.position += 1;
+                    }
+                }
+            }
+            let Ok(value) = self.source[start..self.position].parse() else {
+                return self.error("Invalid number");
+            };
+            self.token = Token::Number(value);
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while bytes
+                .get(self.position)
+                .is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_' || *c == b'.')
+            {
+                self.position += 1;
+            }
+            self.token = Token::Ident(&self.source[start..self.position]);
+        } else if b"+-*/%^(),".contains(&c) {
+            self.position += 1;
+            self.token = Token::Symbol(c);
+        } else {
+            return self.error(format!(
+                "Unexpected character `{}`",
+                self.source[start..].chars().next().unwrap_or_default()
+            ));
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, symbol: u8) -> Result<(), ExprError> {
+        if self.token != Token::Symbol(symbol) {
+            return self.error(format!("Expected `{}`", symbol as char));
+        }
+        self.advance()
+    }
+
+    /// Push an instruction, folding constants.
+    fn emit(&mut self, op: Op) -> Result<(), ExprError> {
+        let arity = op.arity();
+        let start = self.ops.len() - arity;
+        let mut args = [0.; 3];
+        let foldable = arity > 0
+            && self.ops[start..]
+                .iter()
+                .zip(&mut args)
+                .all(|(op, arg)| match op {
+                    Op::Const(value) => {
+                        *arg = *value;
+                        true
+                    }
+                    _ => false,
+                });
+        let op = match op {
+            Op::Unary(f) if foldable => Op::Const(f(args[0])),
+            Op::Binary(f) if foldable => Op::Const(f(args[0], args[1])),
+            Op::Ternary(f) if foldable => Op::Const(f(args[0], args[1], args[2])),
+            op => op,
+        };
+        if foldable {
+            self.ops.truncate(start);
+            self.depth -= arity;
+        }
+        self.depth = self.depth + 1 - op.arity();
+        if self.depth > MAX_STACK {
+            return self.error("Expression too complex");
+        }
+        self.ops.push(op);
+        Ok(())
+    }
+
+    /// `expr := term (('+' | '-') term)*`
+    fn expr(&mut self) -> Result<(), ExprError> {
+        self.term()?;
+        loop {
+            let op: fn(f32, f32) -> f32 = match self.token {
+                Token::Symbol(b'+') => |a, b| a + b,
+                Token::Symbol(b'-') => |a, b| a - b,
+                _ => return Ok(()),
+            };
+            self.advance()?;
+            self.term()?;
+            self.emit(Op::Binary(op))?;
+        }
+    }
+
+    /// `term := unary (('*' | '/' | '%') unary)*`
+    fn term(&mut self) -> Result<(), ExprError> {
+        self.unary()?;
+        loop {
+            let op: fn(f32, f32) -> f32 = match self.token {
+                Token::Symbol(b'*') => |a, b| a * b,
+                Token::Symbol(b'/') => |a, b| a / b,
+                Token::Symbol(b'%') => |a, b| a % b,
+                _ => return Ok(()),
+            };
+            self.advance()?;
+            self.unary()?;
+            self.emit(Op::Binary(op))?;
+        }
+    }
+
+    /// `unary := '-' unary | power`
+    ///
+    /// All recursion passes through `unary`, so this bounds the stack usage of the parser.
+    fn unary(&mut self) -> Result<(), ExprError> {
+        if self.nesting >= MAX_NESTING {
+            return self.error("Expression nested too deeply");
+        }
+        self.nesting += 1;
+        let result = self.unary_inner();
+        self.nesting -= 1;
+        result
+    }
+
+    fn unary_inner(&mut self) -> Result<(), ExprError> {
+        if self.token == Token::Symbol(b'-') {
+            self.advance()?;
+            self.unary()?;
+            self.emit(Op::Unary(|x| -x))
+        } else {
+            self.power()
+        }
+    }
+
+    /// `power := atom ('^' unary)?`
+    fn power(&mut self) -> Result<(), ExprError> {
+        self.atom()?;
+        if self.token == Token::Symbol(b'^') {
+            self.advance()?;
+            self.unary()?;
+            self.emit(Op::Binary(f32::powf))?;
+        }
+        Ok(())
+    }
+
+    /// `atom := number | variable | constant | function '(' args ')' | '(' expr ')'`
+    fn atom(&mut self) -> Result<(), ExprError> {
+        match self.token {
+            Token::Number(value) => {
+                self.advance()?;
+                self.emit(Op::Const(value))
+            }
+            Token::Symbol(b'(') => {
+                self.advance()?;
+                self.expr()?;
+                self.expect(b')')
+            }
+            Token::Ident(name) => {
+                let position = self.token_position;
+                self.advance()?;
+                if self.token != Token::Symbol(b'(') {
+                    if let Some(var) = Var::parse(name) {
+                        return self.emit(Op::Var(var));
+                    }
+                    if let Some(value) = constant(name) {
+                        return self.emit(Op::Const(value));
+                    }
+                    if function(name).is_some() {
+                        return self.error(format!("Expected `(` after `{name}`"));
+                    }
+                    self.token_position = position;
+                    return self.error(format!("Unknown variable `{name}`"));
+                }
+                let Some(op) = function(name) else {
+                    self.token_position = position;
+                    return self.error(format!("Unknown function `{name}`"));
+                };
+                self.advance()?;
+                for i in 0..op.arity() {
+                    if i > 0 {
+                        self.expect(b',')?;
+                    }
+                    self.expr()?;
+                }
+                if self.token != Token::Symbol(b')') {
+                    return self.error(format!(
+                        "Expected `)`, `{name}` takes {} arguments",
+                        op.arity()
+                    ));
+                }
+                self.advance()?;
+                self.emit(op)
+            }
+            Token::End => self.error("Unexpected end of expression"),
+            Token::Symbol(c) => self.error(format!("Unexpected `{}`", c as char)),
+        }
+    }
+}
+
+/// An expression compiled to bytecode, see the [module documentation](self) for syntax.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Expr {
+    source: String,
+    ops: Vec<Op>,
+}
+
+impl Expr {
+    /// Compile an expression.
+    pub fn compile(source: &str) -> Result<Self, ExprError> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+            token: Token::End,
+            token_position: 0,
+            ops: Vec::new(),
+            depth: 0,
+            nesting: 0,
+        };
+        parser.advance()?;
+        parser.expr()?;
+        if parser.token != Token::End {
+            return parser.error("Expected an operator");
+        }
+        Ok(Expr {
+            source: source.to_owned(),
+            ops: parser.ops,
+        })
+    }
+
+    /// Obtain the source of the expression.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the value if the expression is a constant.
+    pub fn as_constant(&self) -> Option<f32> {
+        match self.ops.as_slice() {
+            [Op::Const(value)] => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Evaluate the expression.
+    pub fn eval(&self, vars: &ExprVars) -> f32 {
+        let mut stack = [0.; MAX_STACK];
+        let mut len = 0;
+        for op in &self.ops {
+            match *op {
+                Op::Const(value) => {
+                    stack[len] = value;
+                    len += 1;
+                }
+                Op::Var(var) => {
+                    stack[len] = var.get(vars);
+                    len += 1;
+                }
+                Op::Unary(f) => stack[len - 1] = f(stack[len - 1]),
+                Op::Binary(f) => {
+                    len -= 1;
+                    stack[len - 1] = f(stack[len - 1], stack[len]);
+                }
+                Op::Ternary(f) => {
+                    len -= 2;
+                    stack[len - 1] = f(stack[len - 1], stack[len], stack[len + 1]);
+                }
+            }
+        }
+        stack[0]
+    }
+}
+
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ExprError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Expr::compile(s)
+    }
+}
+
+impl TryFrom<String> for Expr {
+    type Error = ExprError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Expr::compile(&value)
+    }
+}
+
+impl From<Expr> for String {
+    fn from(value: Expr) -> Self {
+        value.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expr, ExprVars};
+
+    fn eval(source: &str) -> f32 {
+        Expr::compile(source).unwrap().eval(&ExprVars::default())
+    }
+
+    fn error(source: &str) -> (usize, String) {
+        let error = Expr::compile(source).unwrap_err();
+        (error.position, error.message)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.);
+        assert_eq!(eval("(1 + 2) * 3"), 9.);
+        assert_eq!(eval("10 - 4 - 3"), 3.);
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.);
+        assert_eq!(eval("-2 ^ 2"), -4.);
+        assert_eq!(eval("2 * -3"), -6.);
+        assert_eq!(eval("7 % 4 * 2"), 6.);
+        assert_eq!(eval("clamp(5, 0, 1 + 1)"), 2.);
+    }
+
+    #[test]
+    fn constant_folding() {
+        assert_eq!(Expr::compile("1 + 2 * 3").unwrap().as_constant(), Some(7.));
+        assert_eq!(
+            Expr::compile("max(pi, 3) * 2").unwrap().as_constant(),
+            Some(std::f32::consts::TAU)
+        );
+        assert_eq!(Expr::compile("age + 1").unwrap().as_constant(), None);
+        let expr = Expr::compile("age * (2 + 2)").unwrap();
+        let vars = ExprVars {
+            age: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(expr.eval(&vars), 2.);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(error("1 +"), (3, "Unexpected end of expression".into()));
+        assert_eq!(error("(1"), (2, "Expected `)`".into()));
+        assert_eq!(error("1 2"), (2, "Expected an operator".into()));
+        assert_eq!(error("1 $ 2"), (2, "Unexpected character `$`".into()));
+        assert_eq!(error("foo + 1"), (0, "Unknown variable `foo`".into()));
+        assert_eq!(error("foo(1)"), (0, "Unknown function `foo`".into()));
+        assert_eq!(error("sin + 1"), (4, "Expected `(` after `sin`".into()));
+        assert_eq!(error("min(1)"), (5, "Expected `,`".into()));
+        assert_eq!(
+            error("min(1, 2, 3)"),
+            (8, "Expected `)`, `min` takes 2 arguments".into())
+        );
+    }
+
+    #[test]
+    fn nesting_limit() {
+        assert_eq!(eval(&format!("{}1{}", "(".repeat(32), ")".repeat(32))), 1.);
+        let parens = format!("{}1{}", "(".repeat(10000), ")".repeat(10000));
+        assert_eq!(error(&parens).1, "Expression nested too deeply");
+        let negations = format!("{}1", "-".repeat(10000));
+        assert_eq!(error(&negations).1, "Expression nested too deeply");
+        let calls = format!("{}1{}", "sin(".repeat(10000), ")".repeat(10000));
+        assert_eq!(error(&calls).1, "Expression nested too deeply");
+    }
+}
@@ -175,18 +175,25 @@ impl From<Entity> for ProjectileRef {
 pub struct HairParticles(Vec<DefaultInstanceBuffer>);
 
 impl HairParticles {
-    pub fn new<P: ProjectileSystem>(mut particles: P) -> Self {
+    /// Spawn particles with a random seed.
+    pub fn new<P: ProjectileSystem>(particles: P) -> Self {
+        Self::with_seed(particles, fastrand::u64(..))
+    }
+
+    /// Spawn particles deterministically from a seed.
+    pub fn with_seed<P: ProjectileSystem>(mut particles: P, seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
         let count = particles.spawn_step(0.);
         let mut buf = Vec::with_capacity(count);
         for _ in 0..count {
-            let seed = particles.rng();
+            let seed = particles.rng(&mut rng);
             let particle = particles.build_particle(seed);
             let mat = particle.get_transform().compute_matrix();
             buf.push(DefaultInstanceBuffer {
                 index: particle.get_index(),
                 lifetime: particle.get_lifetime(),
                 fac: particle.get_fac(),
-                seed,
+                seed: seed.as_f32(),
                 transform_x: mat.row(0),
                 transform_y: mat.row(1),
                 transform_z: mat.row(2),
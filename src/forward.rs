@@ -0,0 +1,67 @@
+use bevy::prelude::{Commands, Component, Entity, Event, EventWriter, Query};
+
+use crate::{ProjectileEvent, ProjectileEventBuffer, ProjectileEventType};
+
+/// Forward events in [`ProjectileEventBuffer`] as [`ProjectileClusterEvent`]s,
+/// runs in [`ProjectileSet::ForwardEvents`](crate::ProjectileSet::ForwardEvents).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component)]
+#[require(ProjectileEventBuffer)]
+pub enum ForwardProjectileEvents {
+    /// Send as buffered events, read via [`EventReader`](bevy::prelude::EventReader).
+    #[default]
+    Events,
+    /// Trigger observers targeting the cluster entity.
+    Observers,
+    /// Send as buffered events and trigger observers.
+    Both,
+}
+
+impl ForwardProjectileEvents {
+    /// Returns `true` if sending buffered events.
+    pub const fn events(&self) -> bool {
+        matches!(self, Self::Events | Self::Both)
+    }
+
+    /// Returns `true` if triggering observers.
+    pub const fn observers(&self) -> bool {
+        matches!(self, Self::Observers | Self::Both)
+    }
+}
+
+/// A [`ProjectileEvent`] forwarded from a [`ProjectileCluster`](crate::ProjectileCluster)
+/// with [`ForwardProjectileEvents`].
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ProjectileClusterEvent {
+    /// Entity of the cluster that emitted the event.
+    pub cluster: Entity,
+    /// The event and its data.
+    pub event: ProjectileEvent,
+}
+
+impl ProjectileClusterEvent {
+    /// Returns the type of the event.
+    pub const fn event_type(&self) -> ProjectileEventType {
+        self.event.event
+    }
+}
+
+pub(crate) fn forward_projectile_events(
+    mut commands: Commands,
+    mut writer: EventWriter<ProjectileClusterEvent>,
+    query: Query<(Entity, &ProjectileEventBuffer, &ForwardProjectileEvents)>,
+) {
+    for (cluster, events, forward) in &query {
+        for event in events.iter() {
+            let event = ProjectileClusterEvent {
+                cluster,
+                event: *event,
+            };
+            if forward.events() {
+                writer.send(event);
+            }
+            if forward.observers() {
+                commands.trigger_targets(event, cluster);
+            }
+        }
+    }
+}
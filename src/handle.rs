@@ -0,0 +1,180 @@
+/// A stable handle to a projectile in a [`ProjectileBuffer`](crate::ProjectileBuffer).
+///
+/// Unlike indices, handles survive particles being moved around during cleanup,
+/// and stop resolving once the projectile is removed or its slot is reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProjectileHandle {
+    slot: u32,
+    generation: u32,
+}
+
+/// Marks a particle without a slot.
+const NO_SLOT: u32 = u32::MAX;
+
+/// Maps [`ProjectileHandle`]s to particle indices.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HandleTable {
+    /// Slot of each particle, parallel to the particle buffer.
+    slot_of: Vec<u32>,
+    /// Particle index and generation of each slot, index is [`NO_SLOT`] if free.
+    slots: Vec<(u32, u32)>,
+    /// Free slots.
+    free: Vec<u32>,
+}
+
+impl HandleTable {
+    /// Create a table with slots assigned to `len` existing particles.
+    pub(crate) fn new(len: usize) -> Self {
+        let mut result = HandleTable::default();
+        for index in 0..len {
+            result.assign(index);
+        }
+        result
+    }
+
+    /// Assign a new slot to the particle at `index`, invalidating the previous one.
+    pub(crate) fn assign(&mut self, index: usize) {
+        if index >= self.slot_of.len() {
+            self.slot_of.resize(index + 1, NO_SLOT);
+        }
+        self.release(self.slot_of[index]);
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot as usize].0 = index as u32;
+                slot
+            }
+            None => {
+                self.slots.push((index as u32, 0));
+                (self.slots.len() - 1) as u32
+            }
+        };
+        self.slot_of[index] = slot;
+    }
+
+    /// Free a slot and bump its generation.
+    fn release(&mut self, slot: u32) {
+        if slot == NO_SLOT {
+            return;
+        }
+        let (index, generation) = &mut self.slots[slot as usize];
+        *index = NO_SLOT;
+        *generation = generation.wrapping_add(1);
+        self.free.push(slot);
+    }
+
+    /// Swap slots of two particles.
+    pub(crate) fn swap(&mut self, a: usize, b: usize) {
+        if a.max(b) >= self.slot_of.len() {
+            return;
+        }
+        self.slot_of.swap(a, b);
+        for index in [a, b] {
+            let slot = self.slot_of[index];
+            if slot != NO_SLOT {
+                self.slots[slot as usize].0 = index as u32;
+            }
+        }
+    }
+
+    /// Free slots of particles beyond `len`.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        while self.slot_of.len() > len {
+            if let Some(slot) = self.slot_of.pop() {
+                self.release(slot);
+            }
+        }
+    }
+
+    /// Obtain the handle of the particle at `index`.
+    pub(crate) fn handle(&self, index: usize) -> Option<ProjectileHandle> {
+        let slot = *self.slot_of.get(index)?;
+        if slot == NO_SLOT {
+            return None;
+        }
+        Some(ProjectileHandle {
+            slot,
+            generation: self.slots[slot as usize].1,
+        })
+    }
+
+    /// Obtain the particle index of a handle, if still valid.
+    pub(crate) fn index(&self, handle: ProjectileHandle) -> Option<usize> {
+        let (index, generation) = *self.slots.get(handle.slot as usize)?;
+        if index == NO_SLOT || generation != handle.generation {
+            return None;
+        }
+        Some(index as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::transform::components::Transform;
+
+    use super::{HandleTable, ProjectileHandle};
+    use crate::{ExpirationState, Projectile, ProjectileBuffer};
+
+    #[derive(Debug, Clone, Copy)]
+    struct Id(u32);
+
+    impl Projectile for Id {
+        fn get_transform(&self) -> Transform {
+            Transform::IDENTITY
+        }
+
+        fn update(&mut self, _: f32) {}
+
+        fn expiration_state(&self) -> ExpirationState {
+            ExpirationState::None
+        }
+    }
+
+    fn id(buffer: &ProjectileBuffer, handle: ProjectileHandle) -> Option<u32> {
+        buffer.get_by_handle::<Id>(handle).map(|x| x.0)
+    }
+
+    #[test]
+    fn kill_swaps_handles() {
+        let mut buffer = ProjectileBuffer::new_retain::<Id>(4);
+        buffer.extend((0..4).map(Id));
+        let handles: Vec<_> = (0..4).map(|i| buffer.handle(i).unwrap()).collect();
+        assert!(buffer.kill::<Id>(handles[1]));
+        assert!(!buffer.kill::<Id>(handles[1]));
+        assert_eq!(id(&buffer, handles[1]), None);
+        assert_eq!(id(&buffer, handles[0]), Some(0));
+        assert_eq!(id(&buffer, handles[2]), Some(2));
+        assert_eq!(id(&buffer, handles[3]), Some(3));
+        assert_eq!(buffer.get::<Id>()[1].0, 3);
+    }
+
+    #[test]
+    fn stale_after_reuse() {
+        let mut buffer = ProjectileBuffer::new_retain::<Id>(4);
+        buffer.extend((0..4).map(Id));
+        let stale = buffer.handle(3).unwrap();
+        assert!(buffer.kill::<Id>(stale));
+        buffer.extend([Id(100)]);
+        let fresh = buffer.handle(3).unwrap();
+        assert_ne!(fresh, stale);
+        assert_eq!(id(&buffer, stale), None);
+        assert_eq!(id(&buffer, fresh), Some(100));
+    }
+
+    #[test]
+    fn table_generations() {
+        let mut table = HandleTable::new(2);
+        let a = table.handle(0).unwrap();
+        let b = table.handle(1).unwrap();
+        table.swap(0, 1);
+        assert_eq!(table.index(a), Some(1));
+        assert_eq!(table.index(b), Some(0));
+        table.truncate(1);
+        assert_eq!(table.index(a), None);
+        table.assign(1);
+        let c = table.handle(1).unwrap();
+        assert_eq!(table.index(a), None);
+        assert_eq!(table.index(c), Some(1));
+        table.assign(1);
+        assert_eq!(table.index(c), None);
+    }
+}
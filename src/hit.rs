@@ -0,0 +1,501 @@
+use std::any::type_name;
+
+use bevy::{
+    log::warn_once,
+    math::{Affine3A, Quat, Vec3},
+    prelude::{Commands, Component, Entity, Event, EventWriter, Query, ResMut, Resource},
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::{
+    collision::{Contact, SKIN},
+    ExpirationState, Projectile, ProjectileBuffer, ProjectileCollider, ProjectileEvent,
+    ProjectileEventBuffer, ProjectileEventType, WorldCollider,
+};
+
+/// Shape of a [`Hurtbox`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HurtboxShape {
+    /// A sphere.
+    Sphere { radius: f32 },
+    /// A world space axis aligned box, rotation is ignored.
+    Aabb { half_size: Vec3 },
+    /// A capsule along local `Y`.
+    Capsule { radius: f32, half_length: f32 },
+}
+
+/// A shape projectiles of clusters with [`ProjectileHitDetection`] can hit,
+/// uses the entity's [`GlobalTransform`].
+///
+/// Scale is ignored, unlike [`ProjectileCollider`] this does not affect projectile movement.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[require(Transform)]
+pub struct Hurtbox {
+    /// Shape of the hurtbox.
+    pub shape: HurtboxShape,
+    /// Layers this hurtbox is on, hit if any bit overlaps [`ProjectileHitDetection::mask`].
+    pub layers: u32,
+}
+
+impl Hurtbox {
+    /// Create a hurtbox on layer `1`.
+    pub const fn new(shape: HurtboxShape) -> Self {
+        Self { shape, layers: 1 }
+    }
+
+    /// Set the layers of the hurtbox.
+    pub const fn with_layers(mut self, layers: u32) -> Self {
+        self.layers = layers;
+        self
+    }
+}
+
+/// A [`Hurtbox`] in world space.
+#[derive(Debug, Clone, Copy)]
+pub struct WorldHurtbox {
+    pub entity: Entity,
+    pub layers: u32,
+    pub collider: WorldCollider,
+}
+
+/// All [`Hurtbox`]es in the world, collected at the start of [`ProjectileSet::Simulate`](crate::ProjectileSet::Simulate).
+#[derive(Debug, Default, Resource)]
+pub struct ProjectileHurtboxes(pub Vec<WorldHurtbox>);
+
+/// Convert a [`Hurtbox`] to world space.
+fn world_hurtbox(entity: Entity, hurtbox: &Hurtbox, transform: &GlobalTransform) -> WorldHurtbox {
+    let (_, rotation, translation) = transform.to_scale_rotation_translation();
+    let (collider, rotation) = match hurtbox.shape {
+        HurtboxShape::Sphere { radius } => (ProjectileCollider::Sphere { radius }, rotation),
+        HurtboxShape::Aabb { half_size } => {
+            (ProjectileCollider::Cuboid { half_size }, Quat::IDENTITY)
+        }
+        HurtboxShape::Capsule {
+            radius,
+            half_length,
+        } => (
+            ProjectileCollider::Capsule {
+                radius,
+                half_length,
+            },
+            rotation,
+        ),
+    };
+    WorldHurtbox {
+        entity,
+        layers: hurtbox.layers,
+        collider: WorldCollider {
+            collider,
+            translation,
+            rotation,
+        },
+    }
+}
+
+pub(crate) fn collect_projectile_hurtboxes(
+    mut hurtboxes: ResMut<ProjectileHurtboxes>,
+    query: Query<(Entity, &Hurtbox, &GlobalTransform)>,
+) {
+    hurtboxes.0.clear();
+    hurtboxes.0.extend(
+        query
+            .iter()
+            .map(|(entity, hurtbox, transform)| world_hurtbox(entity, hurtbox, transform)),
+    );
+}
+
+/// Enables swept hit detection against [`Hurtbox`]es on a [`ProjectileCluster`](crate::ProjectileCluster).
+///
+/// Each projectile is swept from its position before the step to its current position,
+/// projectiles starting inside a hurtbox do not hit it.
+/// Hits are written to [`ProjectileHits`] and sent as [`ProjectileHit`] events.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[require(ProjectileHits)]
+pub struct ProjectileHitDetection {
+    /// Layers to hit, compared against [`Hurtbox::layers`].
+    pub mask: u32,
+    /// Radius of projectiles, hurtboxes are inflated by this amount.
+    pub radius: f32,
+    /// If true, expire the projectile at the first hit with [`ExpirationState::Collide`],
+    /// otherwise the projectile passes through and can hit multiple hurtboxes.
+    ///
+    /// Requires [`Projectile::as_physics_mut`], moves the projectile to the contact point
+    /// and calls [`PhysicsProjectile::expire`](crate::PhysicsProjectile::expire).
+    pub expire: bool,
+}
+
+impl Default for ProjectileHitDetection {
+    fn default() -> Self {
+        Self::new(u32::MAX)
+    }
+}
+
+impl ProjectileHitDetection {
+    /// Create a hit detection component that hits layers in `mask`, with zero radius.
+    pub const fn new(mask: u32) -> Self {
+        Self {
+            mask,
+            radius: 0.,
+            expire: false,
+        }
+    }
+
+    /// Set the radius of projectiles.
+    pub const fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Expire projectiles on the first hit.
+    pub const fn expiring(mut self) -> Self {
+        self.expire = true;
+        self
+    }
+}
+
+/// A projectile hitting a [`Hurtbox`].
+///
+/// Sent as a buffered event and triggered as an observer event targeting the hurtbox entity,
+/// in [`ProjectileSet::ForwardEvents`](crate::ProjectileSet::ForwardEvents).
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ProjectileHit {
+    /// Entity of the cluster that owns the projectile.
+    pub cluster: Entity,
+    /// Entity of the [`Hurtbox`].
+    pub target: Entity,
+    /// Data of the projectile, [`ProjectileEvent::position`] is the contact point on the hurtbox
+    /// and [`ProjectileEvent::normal`] the surface normal, in world space.
+    pub event: ProjectileEvent,
+}
+
+/// Hits of a cluster with [`ProjectileHitDetection`] in the last frame.
+#[derive(Debug, Clone, Default, Component)]
+pub struct ProjectileHits(pub Vec<ProjectileHit>);
+
+impl ProjectileHits {
+    /// Iterate over hits in the last frame.
+    pub fn iter(&self) -> impl Iterator<Item = &ProjectileHit> {
+        self.0.iter()
+    }
+}
+
+pub(crate) fn send_projectile_hits(
+    mut commands: Commands,
+    mut writer: EventWriter<ProjectileHit>,
+    query: Query<&ProjectileHits>,
+) {
+    for hits in &query {
+        for hit in hits.iter() {
+            writer.send(*hit);
+            commands.trigger_targets(*hit, hit.target);
+        }
+    }
+}
+
+/// Sweep particles that moved in the last step against hurtboxes.
+///
+/// Requires transforms before the step to be tracked.
+pub(crate) fn hit_particles<P: Projectile>(
+    buffer: &mut ProjectileBuffer,
+    cluster: Entity,
+    hurtboxes: &[WorldHurtbox],
+    detection: &ProjectileHitDetection,
+    to_world: Affine3A,
+    hits: &mut ProjectileHits,
+    mut events: Option<&mut ProjectileEventBuffer>,
+) {
+    if hurtboxes.is_empty() {
+        return;
+    }
+    let to_local = to_world.inverse();
+    let (buf, tracking) = buffer.get_mut_tracked::<P>();
+    let Some(previous) = tracking.previous_mut(buf.len()) else {
+        return;
+    };
+    let mut contacts: Vec<(Entity, Contact)> = Vec::new();
+    for (item, previous) in buf.iter_mut().zip(previous.iter()) {
+        if item.is_expired() {
+            continue;
+        }
+        let from = to_world.transform_point3(previous.translation);
+        let to = to_world.transform_point3(item.get_position());
+        contacts.clear();
+        contacts.extend(
+            hurtboxes
+                .iter()
+                .filter(|x| x.layers & detection.mask != 0)
+                .filter_map(|x| Some((x.entity, x.collider.sweep(from, to, detection.radius)?))),
+        );
+        if contacts.is_empty() {
+            continue;
+        }
+        contacts.sort_by(|(_, a), (_, b)| a.fraction.total_cmp(&b.fraction));
+        if detection.expire {
+            contacts.truncate(1);
+        }
+        let tangent = to_world
+            .transform_vector3(item.get_tangent())
+            .normalize_or_zero();
+        for (target, contact) in &contacts {
+            hits.0.push(ProjectileHit {
+                cluster,
+                target: *target,
+                event: ProjectileEvent {
+                    event: ProjectileEventType::Collide,
+                    seed: item.get_seed(),
+                    index: item.get_index(),
+                    lifetime: item.get_lifetime(),
+                    position: from.lerp(to, contact.fraction) - contact.normal * detection.radius,
+                    tangent,
+                    normal: contact.normal,
+                },
+            });
+        }
+        if !detection.expire {
+            continue;
+        }
+        let contact = contacts[0].1;
+        let point = from.lerp(to, contact.fraction) + contact.normal * SKIN;
+        let Some(physics) = item.as_physics_mut() else {
+            warn_once!(
+                "Expiring hit detection requires `Projectile::as_physics_mut` on {}.",
+                type_name::<P>()
+            );
+            continue;
+        };
+        physics.set_position(to_local.transform_point3(point));
+        physics.expire(ExpirationState::Collide);
+        if let Some(events) = events.as_deref_mut() {
+            let surface = point - contact.normal * (detection.radius + SKIN);
+            events.push(ProjectileEvent {
+                event: ProjectileEventType::Collide,
+                seed: item.get_seed(),
+                index: item.get_index(),
+                lifetime: item.get_lifetime(),
+                position: to_local.transform_point3(surface),
+                tangent: item.get_tangent(),
+                normal: to_local
+                    .transform_vector3(contact.normal)
+                    .normalize_or_zero(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use bevy::{
+        math::{Affine3A, Quat, Vec3},
+        prelude::{Entity, GlobalTransform, Transform},
+    };
+
+    use super::{
+        hit_particles, world_hurtbox, Hurtbox, HurtboxShape, ProjectileHitDetection,
+        ProjectileHits, WorldHurtbox,
+    };
+    use crate::{
+        collision::SKIN, ExpirationState, PhysicsProjectile, Projectile, ProjectileBuffer,
+        ProjectileEventBuffer, ProjectileEventType,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    struct Point(Vec3, ExpirationState);
+
+    impl Projectile for Point {
+        fn get_transform(&self) -> Transform {
+            Transform::from_translation(self.0)
+        }
+
+        fn update(&mut self, _: f32) {}
+
+        fn expiration_state(&self) -> ExpirationState {
+            self.1
+        }
+
+        fn as_physics_mut(&mut self) -> Option<&mut dyn PhysicsProjectile> {
+            Some(self)
+        }
+    }
+
+    impl PhysicsProjectile for Point {
+        fn position(&self) -> Vec3 {
+            self.0
+        }
+
+        fn set_position(&mut self, position: Vec3) {
+            self.0 = position
+        }
+
+        fn velocity(&self) -> Vec3 {
+            Vec3::ZERO
+        }
+
+        fn set_velocity(&mut self, _: Vec3) {}
+
+        fn expire(&mut self, state: ExpirationState) {
+            self.1 = state
+        }
+    }
+
+    fn hurtbox(index: u32, shape: HurtboxShape, transform: Transform) -> WorldHurtbox {
+        world_hurtbox(
+            Entity::from_raw(index),
+            &Hurtbox::new(shape),
+            &GlobalTransform::from(transform),
+        )
+    }
+
+    fn sphere() -> WorldHurtbox {
+        hurtbox(0, HurtboxShape::Sphere { radius: 1. }, Transform::IDENTITY)
+    }
+
+    fn sweep(
+        from: Vec3,
+        to: Vec3,
+        hurtboxes: &[WorldHurtbox],
+        detection: &ProjectileHitDetection,
+        events: Option<&mut ProjectileEventBuffer>,
+    ) -> (Point, ProjectileHits) {
+        let mut buffer = ProjectileBuffer::new_retain::<Point>(1);
+        buffer.track_previous(true);
+        buffer.extend([Point(to, ExpirationState::None)]);
+        let (_, tracking) = buffer.get_mut_tracked::<Point>();
+        tracking.previous_mut(1).unwrap()[0] = Transform::from_translation(from);
+        let mut hits = ProjectileHits::default();
+        hit_particles::<Point>(
+            &mut buffer,
+            Entity::PLACEHOLDER,
+            hurtboxes,
+            detection,
+            Affine3A::IDENTITY,
+            &mut hits,
+            events,
+        );
+        (buffer.get::<Point>()[0], hits)
+    }
+
+    fn hits(from: Vec3, to: Vec3, hurtbox: WorldHurtbox) -> ProjectileHits {
+        sweep(from, to, &[hurtbox], &ProjectileHitDetection::new(1), None).1
+    }
+
+    fn assert_hit(hits: &ProjectileHits, position: Vec3, normal: Vec3) {
+        assert_eq!(hits.iter().count(), 1);
+        let hit = hits.iter().next().unwrap();
+        assert!(
+            hit.event.position.abs_diff_eq(position, 1e-4),
+            "{}",
+            hit.event.position
+        );
+        assert!(
+            hit.event.normal.abs_diff_eq(normal, 1e-4),
+            "{}",
+            hit.event.normal
+        );
+    }
+
+    #[test]
+    fn hit_approaching() {
+        let hits = hits(Vec3::X * 3., Vec3::X * 0.5, sphere());
+        assert_hit(&hits, Vec3::X, Vec3::X);
+    }
+
+    #[test]
+    fn hit_receding() {
+        assert_eq!(hits(Vec3::X * 2., Vec3::X * 3., sphere()).iter().count(), 0);
+    }
+
+    #[test]
+    fn aabb_ignores_rotation() {
+        let transform =
+            Transform::from_xyz(0., 1., 0.).with_rotation(Quat::from_rotation_y(FRAC_PI_4));
+        let aabb = hurtbox(
+            0,
+            HurtboxShape::Aabb {
+                half_size: Vec3::ONE,
+            },
+            transform,
+        );
+        let hits = hits(Vec3::new(3., 1.5, 0.), Vec3::new(0., 1.5, 0.), aabb);
+        assert_hit(&hits, Vec3::new(1., 1.5, 0.), Vec3::X);
+    }
+
+    #[test]
+    fn capsule_hit() {
+        let shape = HurtboxShape::Capsule {
+            radius: 0.5,
+            half_length: 1.,
+        };
+        let upright = hurtbox(0, shape, Transform::IDENTITY);
+        let top = hits(Vec3::Y * 3., Vec3::ZERO, upright);
+        assert_hit(&top, Vec3::Y * 1.5, Vec3::Y);
+        let side = hits(Vec3::new(2., 0.8, 0.), Vec3::new(0., 0.8, 0.), upright);
+        assert_hit(&side, Vec3::new(0.5, 0.8, 0.), Vec3::X);
+        // Lying along `X`, the same path hits the side of the capsule.
+        let lying = hurtbox(
+            0,
+            shape,
+            Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_4 * 2.)),
+        );
+        let top = hits(Vec3::new(0.8, 3., 0.), Vec3::new(0.8, 0., 0.), lying);
+        assert_hit(&top, Vec3::new(0.8, 0.5, 0.), Vec3::Y);
+    }
+
+    #[test]
+    fn hit_layers() {
+        let mut hurtbox = sphere();
+        hurtbox.layers = 2;
+        let detection = ProjectileHitDetection::new(1);
+        let (_, hits) = sweep(Vec3::X * 3., Vec3::ZERO, &[hurtbox], &detection, None);
+        assert_eq!(hits.iter().count(), 0);
+    }
+
+    #[test]
+    fn hit_passes_through() {
+        let far = hurtbox(
+            1,
+            HurtboxShape::Sphere { radius: 1. },
+            Transform::from_xyz(-4., 0., 0.),
+        );
+        let detection = ProjectileHitDetection::new(1);
+        let (point, hits) = sweep(
+            Vec3::X * 3.,
+            Vec3::X * -8.,
+            &[far, sphere()],
+            &detection,
+            None,
+        );
+        let targets: Vec<_> = hits.iter().map(|x| x.target.index()).collect();
+        assert_eq!(targets, [0, 1]);
+        assert_eq!(point.0, Vec3::X * -8.);
+        assert_eq!(point.1, ExpirationState::None);
+    }
+
+    #[test]
+    fn hit_expires() {
+        let far = hurtbox(
+            1,
+            HurtboxShape::Sphere { radius: 1. },
+            Transform::from_xyz(-4., 0., 0.),
+        );
+        let detection = ProjectileHitDetection::new(1).with_radius(0.5).expiring();
+        let mut events = ProjectileEventBuffer::default();
+        let (point, hits) = sweep(
+            Vec3::X * 3.,
+            Vec3::X * -8.,
+            &[far, sphere()],
+            &detection,
+            Some(&mut events),
+        );
+        // Only the first hurtbox is hit.
+        assert_hit(&hits, Vec3::X, Vec3::X);
+        assert_eq!(hits.0[0].target.index(), 0);
+        assert_eq!(point.1, ExpirationState::Collide);
+        assert!(point.0.abs_diff_eq(Vec3::X * (1.5 + SKIN), 1e-4));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, ProjectileEventType::Collide);
+        assert!(events[0].position.abs_diff_eq(Vec3::X, 1e-4));
+        assert!(events[0].normal.abs_diff_eq(Vec3::X, 1e-4));
+    }
+}
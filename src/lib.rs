@@ -11,11 +11,19 @@ use bevy::{
     app::{Plugin, Update},
     asset::Assets,
     color::Srgba,
-    math::Vec3,
+    ecs::{
+        entity::EntityHashSet,
+        schedule::{InternedScheduleLabel, ScheduleLabel, SystemSet},
+    },
+    log::error,
+    math::{Affine3A, Vec3},
     pbr::MaterialPlugin,
-    prelude::{Component, DetectChanges, Entity, IntoSystemConfigs, Query, Ref, Res, Visibility},
+    prelude::{
+        Component, DetectChanges, Entity, IntoSystemConfigs, IntoSystemSetConfigs, Local, Query,
+        Ref, Res, Visibility,
+    },
     render::{render_resource::Shader, ExtractSchedule, Render, RenderApp, RenderSet},
-    time::{Time, Virtual},
+    tasks::{ComputeTaskPool, TaskPool},
     transform::components::{GlobalTransform, Transform},
 };
 use despawn::despawn_projectiles;
@@ -33,6 +41,9 @@ pub mod shader;
 mod sub;
 pub use sub::*;
 mod buffer;
+mod handle;
+pub(crate) use handle::HandleTable;
+pub use handle::ProjectileHandle;
 pub mod trail;
 pub mod util;
 pub use buffer::*;
@@ -40,14 +51,105 @@ use trail::{trail_rendering, TrailMaterial, TrailMeshBuilder};
 mod despawn;
 mod noop;
 pub use despawn::DespawnProjectileCluster;
+pub mod dynamic;
+pub mod expr;
 pub mod templates;
+use templates::{track_homing_targets, update_homing, Homing, HomingTarget};
+mod time;
+pub use time::*;
+mod seed;
+pub use seed::ParticleSeed;
+mod prewarm;
+use prewarm::prewarm_projectiles;
+mod collision;
+use collision::{collect_projectile_colliders, collide_particles};
+mod affector;
+use affector::{apply_affectors, collect_projectile_affectors};
+pub use affector::{AffectorKind, Falloff, ProjectileAffector, ProjectileAffectors, WorldAffector};
+pub use collision::{
+    CollisionResponse, PhysicsProjectile, ProjectileCollider, ProjectileColliders,
+    ProjectileCollision, WorldCollider,
+};
+mod spatial;
+use spatial::build_spatial_index;
+pub use spatial::{ProjectileQueryItem, ProjectileSpatialIndex, ProjectileSpatialQuery};
+mod neighbours;
+use neighbours::neighbour_pass;
+pub use neighbours::Neighbours;
+mod forward;
+use forward::forward_projectile_events;
+pub use forward::{ForwardProjectileEvents, ProjectileClusterEvent};
+mod hit;
+use hit::{collect_projectile_hurtboxes, hit_particles, send_projectile_hits};
+pub use hit::{
+    Hurtbox, HurtboxShape, ProjectileHit, ProjectileHitDetection, ProjectileHits,
+    ProjectileHurtboxes, WorldHurtbox,
+};
+mod replication;
+pub use replication::SpawnRecord;
+mod recording;
+use recording::record_projectiles;
+pub use recording::{
+    ProjectileRecorder, ProjectileRecording, ProjectileReplay, RecordedFrame, ReplayedProjectile,
+};
+#[cfg(feature = "asset")]
+pub mod asset;
+mod snapshot;
+pub use prewarm::ProjectilePrewarm;
+pub use snapshot::{
+    BufferSnapshot, ClusterSnapshot, ErasedSnapshotProjectileSystem, SnapshotProjectileSystem,
+};
 
 /// Plugin for `berdicle`.
 ///
 /// Adds support for [`StandardParticle`],
 /// other particle materials must be manually added via
 /// [`InstancedMaterialPlugin`].
-pub struct ProjectilePlugin;
+///
+/// Systems are added to [`ProjectileSet`]s in [`Update`] by default,
+/// use [`ProjectilePlugin::new`] to run them in a different schedule.
+#[derive(Debug, Clone)]
+pub struct ProjectilePlugin {
+    /// Schedule to run the simulation in.
+    pub schedule: InternedScheduleLabel,
+}
+
+impl Default for ProjectilePlugin {
+    fn default() -> Self {
+        Self {
+            schedule: Update.intern(),
+        }
+    }
+}
+
+impl ProjectilePlugin {
+    /// Run the simulation in a schedule, i.e. `FixedUpdate` or `PostUpdate`.
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+/// System sets of `berdicle`, run in order in [`ProjectilePlugin::schedule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum ProjectileSet {
+    /// Advance time on [`ProjectileCluster`]s and write to [`ProjectileEventBuffer`]s.
+    Simulate,
+    /// Send [`ProjectileClusterEvent`]s from clusters with [`ForwardProjectileEvents`]
+    /// and [`ProjectileHit`]s from clusters with [`ProjectileHitDetection`],
+    /// write frames of [`ProjectileRecorder`]s.
+    ForwardEvents,
+    /// Spawn projectiles of [`SubProjectileSystem`]s and [`EventProjectileSystem`]s
+    /// from their [`ProjectileParent`]s.
+    SpawnChildren,
+    /// Rebuild [`ProjectileSpatialIndex`]es.
+    SpatialIndex,
+    /// Build trail meshes.
+    Trails,
+    /// Despawn finished clusters with [`DespawnProjectileCluster`].
+    Despawn,
+}
 
 impl Plugin for ProjectilePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
@@ -74,11 +176,68 @@ impl Plugin for ProjectilePlugin {
         );
         app.add_plugins(MaterialPlugin::<TrailMaterial>::default());
         app.add_plugins(InstancedMaterialPlugin::<StandardParticle>::default());
-        app.add_systems(Update, projectile_simulation_system);
-        app.add_systems(Update, trail_rendering.after(projectile_simulation_system));
+        app.init_resource::<ProjectileTimestep>();
+        app.init_resource::<ProjectileColliders>();
+        app.init_resource::<ProjectileAffectors>();
+        app.init_resource::<ProjectileHurtboxes>();
+        app.add_event::<ProjectileClusterEvent>();
+        app.add_event::<ProjectileHit>();
+        app.configure_sets(
+            self.schedule,
+            (
+                ProjectileSet::Simulate,
+                ProjectileSet::ForwardEvents,
+                ProjectileSet::SpawnChildren,
+                ProjectileSet::SpatialIndex,
+                ProjectileSet::Trails,
+                ProjectileSet::Despawn,
+            )
+                .chain(),
+        );
+        #[cfg(feature = "asset")]
+        {
+            use bevy::asset::AssetApp;
+            app.init_asset::<asset::EmitterAsset>();
+            app.register_asset_loader(asset::EmitterAssetLoader);
+            app.add_systems(
+                self.schedule,
+                asset::sync_projectile_emitters.before(ProjectileSet::Simulate),
+            );
+        }
+        app.add_systems(
+            self.schedule,
+            (
+                collect_projectile_colliders,
+                collect_projectile_affectors,
+                collect_projectile_hurtboxes,
+                track_homing_targets,
+                prewarm_projectiles,
+                projectile_simulation_system,
+            )
+                .chain()
+                .in_set(ProjectileSet::Simulate),
+        );
+        app.add_systems(
+            self.schedule,
+            (
+                forward_projectile_events,
+                send_projectile_hits,
+                record_projectiles,
+            )
+                .in_set(ProjectileSet::ForwardEvents),
+        );
+        app.add_systems(
+            self.schedule,
+            projectile_spawn_children_system.in_set(ProjectileSet::SpawnChildren),
+        );
+        app.add_systems(
+            self.schedule,
+            build_spatial_index.in_set(ProjectileSet::SpatialIndex),
+        );
+        app.add_systems(self.schedule, trail_rendering.in_set(ProjectileSet::Trails));
         app.add_systems(
-            Update,
-            despawn_projectiles.after(projectile_simulation_system),
+            self.schedule,
+            despawn_projectiles.in_set(ProjectileSet::Despawn),
         );
         app.sub_app_mut(RenderApp)
             .add_systems(ExtractSchedule, (extract_clean, extract_buffers).chain())
@@ -89,62 +248,240 @@ impl Plugin for ProjectilePlugin {
     }
 }
 
-/// The main system of `berdicle`, runs in [`Update`].
+/// The main system of `berdicle`, runs in [`ProjectileSet::Simulate`].
+///
+/// See [`ProjectileTimestep`] for running the simulation at a fixed rate,
+/// [`ProjectileClock`] for per cluster time control
+/// [`ProjectileCollision`] for colliding with [`ProjectileCollider`]s
+/// [`ProjectileHitDetection`] for hitting [`Hurtbox`]es
+/// [`ProjectileAffector`] for force fields
+/// and [`Homing`](templates::Homing) for tracking target entities.
 pub fn projectile_simulation_system(
-    time: Res<Time<Virtual>>,
+    clocks: ProjectileClocks,
+    timestep: Res<ProjectileTimestep>,
+    colliders: Res<ProjectileColliders>,
+    affectors: Res<ProjectileAffectors>,
+    hurtboxes: Res<ProjectileHurtboxes>,
+    targets: Query<(&GlobalTransform, Option<&HomingTarget>)>,
     mut particles: Query<(
         Entity,
         &mut ProjectileCluster,
         &mut ProjectileBuffer,
         Ref<GlobalTransform>,
         Option<&mut ProjectileEventBuffer>,
-        Option<&ProjectileParent>,
+        Option<&ProjectileClock>,
+        Option<&ProjectileRngSeed>,
+        Option<&ProjectileCollision>,
+        Option<(&ProjectileHitDetection, &mut ProjectileHits)>,
     )>,
 ) {
-    let dt = time.delta_secs();
-    particles
-        .par_iter_mut()
-        .for_each(|(_, mut system, mut buffer, transform, events, _)| {
+    let targets = |entity| homing_target(&targets, entity);
+    let world = StepWorld {
+        colliders: &colliders.0,
+        affectors: &affectors.0,
+        hurtboxes: &hurtboxes.0,
+        targets: &targets,
+    };
+    particles.par_iter_mut().for_each(
+        |(
+            entity,
+            mut system,
+            mut buffer,
+            transform,
+            mut events,
+            clock,
+            seed,
+            collision,
+            mut hit,
+        )| {
             if buffer.is_uninit() {
-                *buffer = system.spawn_particle_buffer();
+                init_particle_buffer(&system, &mut buffer, entity, seed);
             }
             if transform.is_changed() && system.is_world_space() {
                 system.update_position(&transform)
             }
-            if let Some(mut events) = events {
+            if let Some((_, hits)) = &mut hit {
+                hits.0.clear();
+            }
+            let Some(dt) = clocks.delta(clock) else {
+                buffer.delta = 0.;
+                if let Some(mut events) = events {
+                    events.clear();
+                }
+                return;
+            };
+            buffer.track_previous(timestep.is_fixed() || collision.is_some() || hit.is_some());
+            let (steps, dt) = timestep.advance(&mut buffer, dt);
+            if let Some(events) = events.as_deref_mut() {
                 events.clear();
-                system.update_with_event_buffer(dt, &mut buffer, &mut events);
-            } else {
-                system.update(dt, &mut buffer);
             }
-        });
+            for _ in 0..steps {
+                step_cluster(
+                    entity,
+                    &mut system,
+                    &mut buffer,
+                    &transform,
+                    dt,
+                    &world,
+                    collision,
+                    hit.as_mut()
+                        .map(|(detection, hits)| (*detection, &mut **hits)),
+                    events.as_deref_mut(),
+                );
+            }
+        },
+    );
+}
 
-    // Safety: parent is checked to not be the same entity.
-    for (entity, mut system, mut buffer, _, _, parent) in unsafe { particles.iter_unsafe() } {
-        let Some(ProjectileParent(parent)) = parent else {
+/// Obtain position and velocity of a homing target.
+pub(crate) fn homing_target(
+    targets: &Query<(&GlobalTransform, Option<&HomingTarget>)>,
+    entity: Entity,
+) -> Option<(Vec3, Vec3)> {
+    let (transform, target) = targets.get(entity).ok()?;
+    Some((
+        transform.translation(),
+        target.map_or(Vec3::ZERO, |x| x.velocity),
+    ))
+}
+
+/// Spawns projectiles of child clusters from their parents,
+/// runs in [`ProjectileSet::SpawnChildren`].
+///
+/// Parents are processed before their children,
+/// clusters in a [`ProjectileParent`] cycle are reported as errors and skipped.
+pub fn projectile_spawn_children_system(
+    mut reported: Local<EntityHashSet>,
+    particles: Query<(
+        Entity,
+        &mut ProjectileCluster,
+        &mut ProjectileBuffer,
+        Option<&ProjectileEventBuffer>,
+        Option<&ProjectileParent>,
+        Option<&ProjectileClock>,
+    )>,
+) {
+    let (order, cycles) = parent_order(
+        particles
+            .iter()
+            .filter_map(|(entity, _, _, _, parent, _)| Some((entity, parent?.0))),
+    );
+    report_parent_cycles(&mut reported, cycles);
+    for (entity, parent) in order {
+        // Safety: entities in `order` are not part of a cycle, so `entity != parent`.
+        let (Ok((_, mut system, mut buffer, _, _, clock)), Ok((_, _, mut parent, events, _, _))) =
+            (unsafe {
+                (
+                    particles.get_unchecked(entity),
+                    particles.get_unchecked(parent),
+                )
+            })
+        else {
             continue;
         };
-        if entity == *parent {
-            panic!("ParticleSystem's parent cannot be itself.")
+        if clock.is_some_and(|x| x.paused) {
+            continue;
         }
-        if let Some(sub) = system.as_sub_particle_system() {
-            // Safety: parent is checked to not be the same entity.
-            let Ok((_, _, mut parent, _, _, _)) = (unsafe { particles.get_unchecked(*parent) })
-            else {
-                continue;
-            };
-            sub.spawn_from_parent(dt, &mut buffer, &mut parent);
+        if buffer.is_uninit() || parent.is_uninit() {
+            continue;
         }
-        if let Some(sub) = system.as_event_particle_system() {
-            let Ok((_, _, _, _, Some(parent), _)) = particles.get(*parent) else {
-                continue;
-            };
-            sub.spawn_on_event(&mut buffer, parent);
+        let dt = buffer.delta;
+        spawn_from_parent(&mut system, &mut buffer, dt, &mut parent, events);
+    }
+}
+
+/// Log an error for clusters in a [`ProjectileParent`] cycle, once per entity.
+pub(crate) fn report_parent_cycles(reported: &mut EntityHashSet, cycles: Vec<Entity>) {
+    for entity in cycles {
+        if reported.insert(entity) {
+            error!(
+                "ProjectileParent of {entity} forms a cycle, \
+                the cluster will not spawn from its parent."
+            );
         }
     }
 }
 
-fn sort_unstable<T>(buf: &mut [T], mut key: impl FnMut(&T) -> bool) {
+/// Initialize the buffer of a cluster and seed its random number generator.
+pub(crate) fn init_particle_buffer(
+    system: &ProjectileCluster,
+    buffer: &mut ProjectileBuffer,
+    entity: Entity,
+    seed: Option<&ProjectileRngSeed>,
+) {
+    *buffer = system.spawn_particle_buffer();
+    buffer.seed_rng(seed.map_or(entity.to_bits(), |x| x.0));
+}
+
+/// World state [`ProjectileCluster`]s interact with during a step.
+pub(crate) struct StepWorld<'t> {
+    pub colliders: &'t [WorldCollider],
+    pub affectors: &'t [WorldAffector],
+    pub hurtboxes: &'t [WorldHurtbox],
+    pub targets: &'t (dyn Fn(Entity) -> Option<(Vec3, Vec3)> + Sync),
+}
+
+/// Simulate a cluster for one step of `dt`,
+/// applying affectors and homing, then testing collisions and hits.
+pub(crate) fn step_cluster(
+    entity: Entity,
+    system: &mut ProjectileCluster,
+    buffer: &mut ProjectileBuffer,
+    transform: &GlobalTransform,
+    dt: f32,
+    world: &StepWorld,
+    collision: Option<&ProjectileCollision>,
+    hit: Option<(&ProjectileHitDetection, &mut ProjectileHits)>,
+    mut events: Option<&mut ProjectileEventBuffer>,
+) {
+    system.apply_affectors(buffer, world.affectors, transform, dt);
+    system.update_homing(buffer, world.targets, transform, events.as_deref_mut());
+    if let Some(events) = events.as_deref_mut() {
+        system.update_with_event_buffer(dt, buffer, events);
+    } else {
+        system.update(dt, buffer);
+    }
+    if let Some(collision) = collision {
+        system.collide(
+            buffer,
+            world.colliders,
+            collision,
+            transform,
+            events.as_deref_mut(),
+        );
+    }
+    if let Some((detection, hits)) = hit {
+        system.hit(
+            buffer,
+            entity,
+            world.hurtboxes,
+            detection,
+            transform,
+            hits,
+            events,
+        );
+    }
+}
+
+/// Spawn particles of a child cluster from its parent's particles and events.
+pub(crate) fn spawn_from_parent(
+    system: &mut ProjectileCluster,
+    buffer: &mut ProjectileBuffer,
+    dt: f32,
+    parent: &mut ProjectileBuffer,
+    events: Option<&ProjectileEventBuffer>,
+) {
+    if let Some(sub) = system.as_sub_particle_system() {
+        sub.spawn_from_parent(dt, buffer, parent);
+    }
+    if let Some(sub) = system.as_event_particle_system() {
+        if let Some(events) = events {
+            sub.spawn_on_event(buffer, events);
+        }
+    }
+}
+
+fn sort_unstable<T>(buf: &mut [T], tracking: &mut Tracking, mut key: impl FnMut(&T) -> bool) {
     if buf.len() < 2 {
         return;
     }
@@ -156,7 +493,8 @@ fn sort_unstable<T>(buf: &mut [T], mut key: impl FnMut(&T) -> bool) {
                 end -= 1;
             }
             if start < end {
-                buf.swap(start, end)
+                buf.swap(start, end);
+                tracking.swap(start, end);
             }
         }
         start += 1;
@@ -169,6 +507,8 @@ pub enum ExpirationState {
     None,
     FadeOut,
     Explode,
+    /// Expired on contact by [`CollisionResponse::Kill`] or [`ProjectileHitDetection::expire`].
+    Collide,
 }
 
 impl ExpirationState {
@@ -196,14 +536,20 @@ impl ExpirationState {
 }
 
 /// A [`Projectile`]. Must be [`Copy`] and have alignment less than `16`.
-pub trait Projectile: Copy + 'static {
+pub trait Projectile: Copy + Send + Sync + 'static {
     // todo: add this back after associated type default
     // /// Instance buffer, [`DefaultInstanceBuffer`] works for most cases.
     // type Extracted: ProjectileInstanceBuffer + for<'t> From<&'t Self>;
 
+    /// If true, use [`Projectile::extract_interpolated`] in [`ProjectileTimestep::Fixed`] mode.
+    ///
+    /// Set to `false` if [`Projectile::extract`] is overridden but [`Projectile::extract_interpolated`] is not,
+    /// since the default produces a [`DefaultInstanceBuffer`].
+    const INTERPOLATE: bool = true;
+
     /// Obtain the seed used to generate the particle.
-    fn get_seed(&self) -> f32 {
-        0.
+    fn get_seed(&self) -> ParticleSeed {
+        ParticleSeed::ZERO
     }
     /// Obtain the index of the particle inserted, optional.
     fn get_index(&self) -> u32 {
@@ -251,6 +597,7 @@ pub trait Projectile: Copy + 'static {
                 lifetime: self.get_lifetime(),
                 position: self.get_position(),
                 tangent: self.get_tangent(),
+                normal: Vec3::ZERO,
             })
         }
     }
@@ -275,10 +622,44 @@ pub trait Projectile: Copy + 'static {
         &[]
     }
 
+    /// Obtain position and velocity for physics, required for [`ProjectileCollision`].
+    fn as_physics_mut(&mut self) -> Option<&mut dyn PhysicsProjectile> {
+        None
+    }
+
+    /// Obtain homing state, enables tracking target entities.
+    fn as_homing_mut(&mut self) -> Option<&mut Homing> {
+        None
+    }
+
     /// Extract to an instance buffer, by default [`DefaultInstanceBuffer`].
     fn extract(&self) -> impl ProjectileInstanceBuffer {
         DefaultInstanceBuffer::from(self)
     }
+
+    /// Extract to an instance buffer with an interpolated transform,
+    /// used in [`ProjectileTimestep::Fixed`] mode if [`Projectile::INTERPOLATE`] is true.
+    ///
+    /// Must produce the same instance buffer as [`Projectile::extract`],
+    /// if one is overridden, the other should be as well.
+    fn extract_interpolated(&self, transform: Transform) -> impl ProjectileInstanceBuffer {
+        DefaultInstanceBuffer::from_transform(self, transform)
+    }
+}
+
+/// Write [`Projectile::extract`], or [`Projectile::extract_interpolated`] if `transform` is specified
+/// and [`Projectile::INTERPOLATE`] is true.
+pub(crate) fn extract_projectile<P: Projectile>(
+    particle: &P,
+    transform: Option<Transform>,
+    bytes: &mut Vec<u8>,
+) {
+    match transform {
+        Some(transform) if P::INTERPOLATE => bytes.extend(bytemuck::bytes_of(
+            &particle.extract_interpolated(transform),
+        )),
+        _ => bytes.extend(bytemuck::bytes_of(&particle.extract())),
+    }
 }
 
 /// A particle spawner type.
@@ -292,10 +673,18 @@ pub trait ProjectileSystem {
     /// * Retain(default): Remove expired particles by moving alive particles in front.
     /// * RingBuffer: Particles are not removed explicitly, but can expire and be reused later.
     ///   Should only be used if lifetime is constant and capacity is well predicted.
+    /// * Growable: Same as Retain, but reallocates instead of discarding particles when full.
     ///
     /// If rendering trails using ring buffer, capacity for detached trails should be reserved.
     const STRATEGY: ParticleBufferStrategy = ParticleBufferStrategy::Retain;
 
+    /// If set, particles are updated in parallel on the [`ComputeTaskPool`]
+    /// in chunks of this size, i.e. `Some(4096)`.
+    ///
+    /// Only worth enabling for clusters with tens of thousands of particles,
+    /// [`ProjectileSystem::on_update`] and spawning are still serial.
+    const PARALLEL_CHUNK_SIZE: Option<usize> = None;
+
     /// Particle type of the system.
     ///
     /// # Panics
@@ -315,12 +704,25 @@ pub trait ProjectileSystem {
     /// Obtain the capacity of the buffer, this value is read once upon initialization
     /// and will not be changed during simulation.
     ///
+    /// If using [`ParticleBufferStrategy::Growable`], this is the initial capacity.
+    ///
     /// We might increment this value by a little bit for alignment.
     fn capacity(&self) -> usize;
 
-    /// Generate a random `0.0..=1.0` number as a seed.
-    fn rng(&mut self) -> f32 {
-        fastrand::f32()
+    /// Obtain what to do when spawning into a full buffer,
+    /// this value is read once upon initialization.
+    ///
+    /// Number of particles lost is counted by [`ProjectileBuffer::dropped`].
+    fn overflow_policy(&self) -> OverflowPolicy {
+        OverflowPolicy::DropNew
+    }
+
+    /// Generate a random seed.
+    ///
+    /// `rng` is the cluster's own random number stream,
+    /// see [`ProjectileRngSeed`] for making simulations deterministic.
+    fn rng(&mut self, rng: &mut fastrand::Rng) -> ParticleSeed {
+        ParticleSeed::from_rng(rng)
     }
 
     /// Determines how many particles to spawn when a time step passes.
@@ -333,11 +735,43 @@ pub trait ProjectileSystem {
     ///
     /// If `spawn_step` is always `0`,
     /// it's safe to implement with [`unreachable!`].
-    fn build_particle(&self, seed: f32) -> Self::Projectile;
+    fn build_particle(&self, seed: ParticleSeed) -> Self::Projectile;
+
+    /// Write the instance buffer of a particle to `bytes`, by default [`Projectile::extract`],
+    /// or [`Projectile::extract_interpolated`] if `transform` is specified.
+    ///
+    /// Override to extract with data from the system,
+    /// see [`DynamicProjectileSystem`](dynamic::DynamicProjectileSystem).
+    fn extract_particle(
+        &self,
+        particle: &Self::Projectile,
+        transform: Option<Transform>,
+        bytes: &mut Vec<u8>,
+    ) {
+        extract_projectile(particle, transform, bytes)
+    }
 
     /// Additional actions to perform during update.
     fn on_update(&mut self, dt: f32, buffer: &mut ProjectileBuffer) {}
 
+    /// If `Some`, enables [`ProjectileSystem::update_with_neighbours`]
+    /// with neighbours within this radius.
+    fn neighbour_radius(&self) -> Option<f32> {
+        None
+    }
+
+    /// A second update pass with read access to neighbours within [`ProjectileSystem::neighbour_radius`],
+    /// runs after [`Projectile::update`] on projectiles that have not expired.
+    ///
+    /// See [`Flocking`](templates::Flocking) for separation, alignment and cohesion.
+    fn update_with_neighbours(
+        &self,
+        particle: &mut Self::Projectile,
+        neighbours: Neighbours<Self::Projectile>,
+        dt: f32,
+    ) {
+    }
+
     /// Perform a meta action on the ParticleSystem.
     ///
     /// Since [`ProjectileCluster`] is type erased, this is the standard way to modify
@@ -382,6 +816,11 @@ pub trait ProjectileSystem {
     fn as_event_particle_system(&mut self) -> Option<&mut dyn ErasedEventParticleSystem> {
         None
     }
+
+    /// Downcast into a [`SnapshotProjectileSystem`].
+    fn as_snapshot_system(&mut self) -> Option<&mut dyn ErasedSnapshotProjectileSystem> {
+        None
+    }
 }
 
 /// Type erased version of [`ProjectileSystem`].
@@ -405,6 +844,42 @@ pub trait ErasedParticleSystem: Send + Sync {
         buffer: &mut ProjectileBuffer,
         events: &mut ProjectileEventBuffer,
     );
+    /// Apply force fields to velocities of particles.
+    fn apply_affectors(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        affectors: &[WorldAffector],
+        transform: &GlobalTransform,
+        dt: f32,
+    );
+    /// Write target positions to [`Homing`](templates::Homing) projectiles.
+    fn update_homing(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        targets: &dyn Fn(Entity) -> Option<(Vec3, Vec3)>,
+        transform: &GlobalTransform,
+        events: Option<&mut ProjectileEventBuffer>,
+    );
+    /// Test particles moved in the last step against colliders.
+    fn collide(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        colliders: &[WorldCollider],
+        collision: &ProjectileCollision,
+        transform: &GlobalTransform,
+        events: Option<&mut ProjectileEventBuffer>,
+    );
+    /// Sweep particles moved in the last step against hurtboxes, write hits of `cluster`.
+    fn hit(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        cluster: Entity,
+        hurtboxes: &[WorldHurtbox],
+        detection: &ProjectileHitDetection,
+        transform: &GlobalTransform,
+        hits: &mut ProjectileHits,
+        events: Option<&mut ProjectileEventBuffer>,
+    );
     /// Create an empty [`ProjectileBuffer`].
     fn spawn_particle_buffer(&self) -> ProjectileBuffer;
     /// Update the global position of the spawner.
@@ -416,10 +891,16 @@ pub trait ErasedParticleSystem: Send + Sync {
     fn apply_meta(&mut self, command: &dyn Any, buffer: &mut ProjectileBuffer);
     /// Extract into a instance buffer.
     fn extract(&self, buffer: &ProjectileBuffer, vec: &mut ErasedExtractBuffer);
+    /// Write indices and positions of projectiles that have not expired.
+    fn positions(&self, buffer: &ProjectileBuffer, out: &mut Vec<(usize, Vec3)>);
+    /// Write [`DefaultInstanceBuffer`]s of projectiles that have not expired.
+    fn instances(&self, buffer: &ProjectileBuffer, out: &mut Vec<DefaultInstanceBuffer>);
     /// Downcast into a [`SubProjectileSystem`];
     fn as_sub_particle_system(&mut self) -> Option<&mut dyn ErasedSubParticleSystem>;
     /// Downcast into a [`EventProjectileSystem`];
     fn as_event_particle_system(&mut self) -> Option<&mut dyn ErasedEventParticleSystem>;
+    /// Downcast into a [`SnapshotProjectileSystem`];
+    fn as_snapshot_system(&mut self) -> Option<&mut dyn ErasedSnapshotProjectileSystem>;
     /// Checks if all particles and trails are despawned.
     ///
     /// Be careful this is usually true on the first frame as well.
@@ -431,6 +912,13 @@ pub trait ErasedParticleSystem: Send + Sync {
 #[require(ProjectileBuffer, Transform, Visibility)]
 pub struct ProjectileCluster(Box<dyn ErasedParticleSystem>);
 
+/// Seed of a [`ProjectileCluster`]'s random number stream,
+/// read once when the [`ProjectileBuffer`] is initialized.
+///
+/// If not present, the stream is seeded from the [`Entity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ProjectileRngSeed(pub u64);
+
 impl Default for ProjectileCluster {
     fn default() -> Self {
         ProjectileCluster::new(NoopParticleSystem)
@@ -469,9 +957,137 @@ impl DerefMut for ProjectileCluster {
     }
 }
 
-fn spawn_particle<T: ProjectileSystem>(particles: &mut T) -> T::Projectile {
-    let seed = particles.rng();
-    particles.build_particle(seed)
+fn spawn_particles<T: ProjectileSystem>(
+    particles: &mut T,
+    buffer: &mut ProjectileBuffer,
+    count: usize,
+) {
+    let mut rng = buffer.rng();
+    let seeds: Vec<_> = (0..count).map(|_| particles.rng(&mut rng)).collect();
+    buffer.set_rng(&rng);
+    for seed in &seeds {
+        buffer.record_spawn(*seed, None);
+    }
+    buffer.extend(seeds.into_iter().map(|seed| particles.build_particle(seed)));
+}
+
+/// Spawn due [`SpawnRecord`]s without a parent and advance them by the time passed.
+fn play_spawns<T: ProjectileSystem>(particles: &mut T, buffer: &mut ProjectileBuffer) {
+    let due = buffer.due_spawns(false);
+    buffer.extend(due.into_iter().map(|(seed, _, age)| {
+        let mut particle = particles.build_particle(seed);
+        if age > 0. {
+            particle.update(age);
+        }
+        particle
+    }));
+}
+
+/// Advance time on all particles, clean up expired particles and spawn new ones.
+fn step_particles<T: ProjectileSystem + Sync>(
+    system: &mut T,
+    dt: f32,
+    buffer: &mut ProjectileBuffer,
+    events: Option<&mut ProjectileEventBuffer>,
+) {
+    let has_events = events.is_some();
+    let original_len = buffer.len;
+    let (buf, tracking) = buffer.get_mut_tracked::<T::Projectile>();
+    let len = update_particles(
+        buf,
+        tracking,
+        T::PARALLEL_CHUNK_SIZE,
+        events,
+        |item, events| match events {
+            Some(events) => {
+                item.update_with_event_buffer(dt, events);
+                !item.is_expired()
+            }
+            None => {
+                item.update(dt);
+                !item.should_despawn()
+            }
+        },
+    );
+    if T::STRATEGY != ParticleBufferStrategy::RingBuffer {
+        if len != original_len {
+            if has_events {
+                sort_unstable(buf, tracking, |x| x.is_expired());
+            } else {
+                sort_unstable(buf, tracking, |x| x.should_despawn());
+            }
+        }
+        tracking.truncate(len);
+    }
+    buffer.len = len;
+    buffer.shrink_unused::<T::Projectile>();
+    if let Some(radius) = system.neighbour_radius() {
+        neighbour_pass(system, dt, buffer, radius);
+    }
+    buffer.advance_replication(dt);
+    if buffer.is_playing_spawns() {
+        play_spawns(system, buffer);
+    } else {
+        let count = system.spawn_step(dt);
+        spawn_particles(system, buffer, count);
+    }
+    system.on_update(dt, buffer)
+}
+
+/// Run `update` on all particles, returns the number of particles alive.
+///
+/// If `chunk_size` is set, particles are updated in parallel on the [`ComputeTaskPool`],
+/// events are written in the same order as the serial version.
+fn update_particles<P: Projectile>(
+    buf: &mut [P],
+    tracking: &mut Tracking,
+    chunk_size: Option<usize>,
+    events: Option<&mut ProjectileEventBuffer>,
+    update: impl Fn(&mut P, Option<&mut ProjectileEventBuffer>) -> bool + Sync,
+) -> usize {
+    fn update_chunk<P: Projectile>(
+        buf: &mut [P],
+        mut previous: Option<&mut [Transform]>,
+        mut events: Option<&mut ProjectileEventBuffer>,
+        update: &(impl Fn(&mut P, Option<&mut ProjectileEventBuffer>) -> bool + Sync),
+    ) -> usize {
+        let mut len = 0;
+        for (idx, item) in buf.iter_mut().enumerate() {
+            if let Some(previous) = previous.as_deref_mut() {
+                previous[idx] = item.get_transform();
+            }
+            len += update(item, events.as_deref_mut()) as usize;
+        }
+        len
+    }
+
+    let previous = tracking.previous_mut(buf.len());
+    let Some(chunk_size) = chunk_size.filter(|size| *size > 0 && buf.len() > *size) else {
+        return update_chunk(buf, previous, events, &update);
+    };
+    let has_events = events.is_some();
+    let update = &update;
+    let mut previous = previous.map(|x| x.chunks_mut(chunk_size));
+    let results = ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+        for chunk in buf.chunks_mut(chunk_size) {
+            let previous = previous.as_mut().and_then(|x| x.next());
+            scope.spawn(async move {
+                let mut events = ProjectileEventBuffer::default();
+                let len = update_chunk(chunk, previous, has_events.then_some(&mut events), update);
+                (len, events)
+            });
+        }
+    });
+    let mut len = 0;
+    if let Some(events) = events {
+        for (count, mut chunk_events) in results {
+            len += count;
+            events.append(&mut chunk_events);
+        }
+    } else {
+        len = results.into_iter().map(|(count, _)| count).sum();
+    }
+    len
 }
 
 impl<T> ErasedParticleSystem for T
@@ -495,33 +1111,7 @@ where
     }
 
     fn update(&mut self, dt: f32, buffer: &mut ProjectileBuffer) {
-        match Self::STRATEGY {
-            ParticleBufferStrategy::Retain => {
-                let original_len = buffer.len;
-                let buf = buffer.get_mut::<T::Projectile>();
-                let mut len = 0;
-                for item in buf.iter_mut() {
-                    item.update(dt);
-                    len += (!item.should_despawn()) as usize
-                }
-                if len != original_len {
-                    sort_unstable(buf, |x| x.should_despawn());
-                }
-                buffer.len = len;
-                buffer.extend((0..self.spawn_step(dt)).map(|_| spawn_particle(self)))
-            }
-            ParticleBufferStrategy::RingBuffer => {
-                let buf = buffer.get_mut::<T::Projectile>();
-                let mut len = 0;
-                for item in buf {
-                    item.update(dt);
-                    len += (!item.should_despawn()) as usize
-                }
-                buffer.len = len;
-                buffer.extend((0..self.spawn_step(dt)).map(|_| spawn_particle(self)))
-            }
-        }
-        self.on_update(dt, buffer)
+        step_particles(self, dt, buffer, None)
     }
 
     fn update_with_event_buffer(
@@ -530,44 +1120,88 @@ where
         buffer: &mut ProjectileBuffer,
         events: &mut ProjectileEventBuffer,
     ) {
-        match Self::STRATEGY {
-            ParticleBufferStrategy::Retain => {
-                let original_len = buffer.len;
-                let buf = buffer.get_mut::<T::Projectile>();
-                let mut len = 0;
-                for item in buf.iter_mut() {
-                    item.update_with_event_buffer(dt, events);
-                    len += (!item.is_expired()) as usize
-                }
-                if len != original_len {
-                    sort_unstable(buf, |x| x.is_expired());
-                }
-                buffer.len = len;
-                buffer.extend((0..self.spawn_step(dt)).map(|_| spawn_particle(self)))
-            }
-            ParticleBufferStrategy::RingBuffer => {
-                let buf = buffer.get_mut::<T::Projectile>();
-                let mut len = 0;
-                for item in buf {
-                    item.update_with_event_buffer(dt, events);
-                    len += (!item.is_expired()) as usize
-                }
-                buffer.len = len;
-                buffer.extend((0..self.spawn_step(dt)).map(|_| spawn_particle(self)))
-            }
-        }
-        self.on_update(dt, buffer)
+        step_particles(self, dt, buffer, Some(events))
+    }
+
+    fn apply_affectors(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        affectors: &[WorldAffector],
+        transform: &GlobalTransform,
+        dt: f32,
+    ) {
+        let to_world = if T::WORLD_SPACE {
+            Affine3A::IDENTITY
+        } else {
+            transform.affine()
+        };
+        apply_affectors::<T::Projectile>(buffer, affectors, to_world, dt)
+    }
+
+    fn update_homing(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        targets: &dyn Fn(Entity) -> Option<(Vec3, Vec3)>,
+        transform: &GlobalTransform,
+        events: Option<&mut ProjectileEventBuffer>,
+    ) {
+        let to_world = if T::WORLD_SPACE {
+            Affine3A::IDENTITY
+        } else {
+            transform.affine()
+        };
+        update_homing::<T::Projectile>(buffer, targets, to_world, events)
+    }
+
+    fn collide(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        colliders: &[WorldCollider],
+        collision: &ProjectileCollision,
+        transform: &GlobalTransform,
+        events: Option<&mut ProjectileEventBuffer>,
+    ) {
+        let to_world = if T::WORLD_SPACE {
+            Affine3A::IDENTITY
+        } else {
+            transform.affine()
+        };
+        collide_particles::<T::Projectile>(buffer, colliders, collision, to_world, events)
+    }
+
+    fn hit(
+        &mut self,
+        buffer: &mut ProjectileBuffer,
+        cluster: Entity,
+        hurtboxes: &[WorldHurtbox],
+        detection: &ProjectileHitDetection,
+        transform: &GlobalTransform,
+        hits: &mut ProjectileHits,
+        events: Option<&mut ProjectileEventBuffer>,
+    ) {
+        let to_world = if T::WORLD_SPACE {
+            Affine3A::IDENTITY
+        } else {
+            transform.affine()
+        };
+        hit_particles::<T::Projectile>(
+            buffer, cluster, hurtboxes, detection, to_world, hits, events,
+        )
     }
 
     fn spawn_particle_buffer(&self) -> ProjectileBuffer {
-        match Self::STRATEGY {
+        let buffer = match Self::STRATEGY {
             ParticleBufferStrategy::Retain => {
                 ProjectileBuffer::new_retain::<T::Projectile>(self.capacity())
             }
             ParticleBufferStrategy::RingBuffer => {
                 ProjectileBuffer::new_ring::<T::Projectile>(self.capacity())
             }
-        }
+            ParticleBufferStrategy::Growable { max } => {
+                ProjectileBuffer::new_growable::<T::Projectile>(self.capacity(), max)
+            }
+        };
+        buffer.with_overflow_policy(self.overflow_policy())
     }
 
     fn update_position(&mut self, transform: &GlobalTransform) {
@@ -581,14 +1215,26 @@ where
     fn extract(&self, buffer: &ProjectileBuffer, extract: &mut ErasedExtractBuffer) {
         let mut count = 0;
         extract.bytes.clear();
-        buffer
-            .get::<T::Projectile>()
-            .iter()
-            .filter(|x| !x.is_expired())
-            .for_each(|x| {
+        let particles = buffer.get::<T::Projectile>();
+        match (buffer.interpolation, &buffer.tracking.previous) {
+            (Some(fac), Some(previous)) => particles
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| !x.is_expired())
+                .for_each(|(idx, x)| {
+                    count += 1;
+                    let current = x.get_transform();
+                    let transform = match previous.get(idx) {
+                        Some(previous) => interpolate_transform(previous, &current, fac),
+                        None => current,
+                    };
+                    self.extract_particle(x, Some(transform), &mut extract.bytes);
+                }),
+            _ => particles.iter().filter(|x| !x.is_expired()).for_each(|x| {
                 count += 1;
-                extract.bytes.extend(bytemuck::bytes_of(&x.extract()));
-            });
+                self.extract_particle(x, None, &mut extract.bytes);
+            }),
+        }
         extract.len = count;
     }
 
@@ -600,6 +1246,31 @@ where
         ProjectileSystem::as_event_particle_system(self)
     }
 
+    fn as_snapshot_system(&mut self) -> Option<&mut dyn ErasedSnapshotProjectileSystem> {
+        ProjectileSystem::as_snapshot_system(self)
+    }
+
+    fn positions(&self, buffer: &ProjectileBuffer, out: &mut Vec<(usize, Vec3)>) {
+        out.extend(
+            buffer
+                .get::<T::Projectile>()
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| !x.is_expired())
+                .map(|(idx, x)| (idx, x.get_position())),
+        )
+    }
+
+    fn instances(&self, buffer: &ProjectileBuffer, out: &mut Vec<DefaultInstanceBuffer>) {
+        out.extend(
+            buffer
+                .get::<T::Projectile>()
+                .iter()
+                .filter(|x| !x.is_expired())
+                .map(DefaultInstanceBuffer::from),
+        )
+    }
+
     fn render_trail(&self, buffer: &ProjectileBuffer, trail: &mut TrailMeshBuilder) {
         buffer
             .get::<T::Projectile>()
@@ -617,3 +1288,151 @@ impl Debug for dyn ErasedParticleSystem {
         self.as_debug().fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        app::{App, FixedUpdate},
+        math::Vec3,
+        prelude::GlobalTransform,
+        render::{mesh::VertexBufferLayout, render_resource::VertexStepMode},
+        time::{Fixed, Time, TimePlugin, TimeUpdateStrategy},
+        transform::components::Transform,
+    };
+    use bytemuck::{Pod, Zeroable};
+
+    use crate::{
+        extract_projectile, projectile_simulation_system, DefaultInstanceBuffer, ExpirationState,
+        ParticleSeed, Projectile, ProjectileAffectors, ProjectileBuffer, ProjectileCluster,
+        ProjectileColliders, ProjectileHurtboxes, ProjectileInstanceBuffer, ProjectileSystem,
+        ProjectileTimestep,
+    };
+
+    #[derive(Debug, Clone, Copy, Zeroable, Pod)]
+    #[repr(C)]
+    struct Position([f32; 3]);
+
+    impl ProjectileInstanceBuffer for Position {
+        fn descriptor() -> VertexBufferLayout {
+            VertexBufferLayout {
+                array_stride: 12,
+                step_mode: VertexStepMode::Instance,
+                attributes: Vec::new(),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Custom(Vec3);
+
+    impl Projectile for Custom {
+        fn get_transform(&self) -> Transform {
+            Transform::from_translation(self.0)
+        }
+
+        fn update(&mut self, _: f32) {}
+
+        fn expiration_state(&self) -> ExpirationState {
+            ExpirationState::None
+        }
+
+        const INTERPOLATE: bool = false;
+
+        fn extract(&self) -> impl ProjectileInstanceBuffer {
+            Position(self.0.to_array())
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Plain(Vec3);
+
+    impl Projectile for Plain {
+        fn get_transform(&self) -> Transform {
+            Transform::from_translation(self.0)
+        }
+
+        fn update(&mut self, dt: f32) {
+            self.0.x += dt;
+        }
+
+        fn expiration_state(&self) -> ExpirationState {
+            ExpirationState::None
+        }
+    }
+
+    #[test]
+    fn interpolated_extract_keeps_custom_layout() {
+        let mut bytes = Vec::new();
+        let transform = Transform::from_xyz(4., 5., 6.);
+        extract_projectile(&Custom(Vec3::ONE), Some(transform), &mut bytes);
+        assert_eq!(bytes, bytemuck::bytes_of(&Position([1.; 3])));
+    }
+
+    #[test]
+    fn interpolated_extract_uses_transform() {
+        let mut bytes = Vec::new();
+        let transform = Transform::from_xyz(4., 5., 6.);
+        extract_projectile(&Plain(Vec3::ONE), Some(transform), &mut bytes);
+        let instance: DefaultInstanceBuffer = bytemuck::pod_read_unaligned(&bytes);
+        assert_eq!(instance.transform_x.w, 4.);
+        assert_eq!(instance.transform_z.w, 6.);
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Mover(bool);
+
+    impl ProjectileSystem for Mover {
+        type Projectile = Plain;
+
+        fn capacity(&self) -> usize {
+            1
+        }
+
+        fn spawn_step(&mut self, _: f32) -> usize {
+            std::mem::replace(&mut self.0, false) as usize
+        }
+
+        fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
+            Plain(Vec3::ZERO)
+        }
+    }
+
+    #[test]
+    fn fixed_update_advances_by_fixed_timestep() {
+        let mut app = App::new();
+        app.add_plugins(TimePlugin);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        app.init_resource::<ProjectileTimestep>();
+        app.init_resource::<ProjectileColliders>();
+        app.init_resource::<ProjectileAffectors>();
+        app.init_resource::<ProjectileHurtboxes>();
+        app.add_systems(FixedUpdate, projectile_simulation_system);
+        let entity = app
+            .world_mut()
+            .spawn((
+                ProjectileCluster::new(Mover(true)),
+                ProjectileBuffer::default(),
+                GlobalTransform::IDENTITY,
+            ))
+            .id();
+        let measure = |app: &App| {
+            let buffer = app.world().get::<ProjectileBuffer>(entity).unwrap();
+            let x = buffer.get::<Plain>().first().map_or(0., |p| p.0.x);
+            (x, app.world().resource::<Time<Fixed>>().elapsed_secs())
+        };
+        for _ in 0..5 {
+            app.update();
+        }
+        let (x0, t0) = measure(&app);
+        for _ in 0..20 {
+            app.update();
+        }
+        let (x1, t1) = measure(&app);
+        assert!(t1 - t0 > 1.5);
+        assert!(((x1 - x0) - (t1 - t0)).abs() < 1e-3);
+    }
+}
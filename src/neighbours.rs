@@ -0,0 +1,302 @@
+use std::slice;
+
+use bevy::tasks::{ComputeTaskPool, TaskPool};
+
+use crate::{
+    Align16MaybeUninit, Projectile, ProjectileBuffer, ProjectileSpatialIndex, ProjectileSystem,
+};
+
+/// Read only access to projectiles near a projectile,
+/// see [`ProjectileSystem::update_with_neighbours`].
+///
+/// Neighbours are copies taken before the neighbour pass started,
+/// so the result does not depend on update order.
+#[derive(Debug, Clone, Copy)]
+pub struct Neighbours<'t, T> {
+    particles: &'t [T],
+    indices: &'t [usize],
+}
+
+impl<'t, T> Neighbours<'t, T> {
+    /// Iterate over neighbours, excluding the projectile itself.
+    pub fn iter(&self) -> impl Iterator<Item = &'t T> + Clone + 't {
+        let particles = self.particles;
+        self.indices.iter().map(move |x| &particles[*x])
+    }
+
+    /// Returns the number of neighbours.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns `true` if there are no neighbours.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+/// Allocations of the neighbour pass, kept between steps.
+#[derive(Debug, Default)]
+pub(crate) struct NeighbourScratch {
+    /// Copy of the particles taken before the pass.
+    particles: Vec<Align16MaybeUninit>,
+    /// Grid over the copy, cell size is the neighbour radius.
+    grid: Option<ProjectileSpatialIndex>,
+}
+
+/// Run [`ProjectileSystem::update_with_neighbours`] on all projectiles that have not expired.
+pub(crate) fn neighbour_pass<T: ProjectileSystem + Sync>(
+    system: &T,
+    dt: f32,
+    buffer: &mut ProjectileBuffer,
+    radius: f32,
+) {
+    if !(radius > 0. && radius.is_finite()) {
+        return;
+    }
+    let mut scratch = std::mem::take(&mut buffer.neighbours);
+    let particles = buffer.get::<T::Projectile>();
+    let len = particles.len();
+    let words = size_of_val(particles).div_ceil(16);
+    scratch.particles.clear();
+    scratch.particles.extend_from_slice(&buffer.buffer[..words]);
+    // Safety: the copy has the buffer's alignment and holds `len` initialized particles.
+    let snapshot =
+        unsafe { slice::from_raw_parts(scratch.particles.as_ptr() as *const T::Projectile, len) };
+    let grid = match &mut scratch.grid {
+        Some(grid) => {
+            if grid.cell_size() != radius {
+                grid.set_cell_size(radius);
+            }
+            grid
+        }
+        grid => grid.insert(ProjectileSpatialIndex::new(radius)),
+    };
+    grid.rebuild(
+        snapshot
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| !x.is_expired())
+            .map(|(idx, x)| (idx, x.get_position())),
+    );
+    let update_chunk = |offset: usize, chunk: &mut [T::Projectile]| {
+        let mut indices = Vec::new();
+        for (idx, particle) in chunk.iter_mut().enumerate() {
+            let idx = idx + offset;
+            if snapshot[idx].is_expired() {
+                continue;
+            }
+            indices.clear();
+            grid.for_each_in_sphere(snapshot[idx].get_position(), radius, |other, _| {
+                if other != idx {
+                    indices.push(other)
+                }
+            });
+            let neighbours = Neighbours {
+                particles: snapshot,
+                indices: &indices,
+            };
+            system.update_with_neighbours(particle, neighbours, dt);
+        }
+    };
+    let particles = buffer.get_mut::<T::Projectile>();
+    match T::PARALLEL_CHUNK_SIZE.filter(|size| *size > 0 && particles.len() > *size) {
+        Some(chunk_size) => {
+            let update_chunk = &update_chunk;
+            ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+                for (idx, chunk) in particles.chunks_mut(chunk_size).enumerate() {
+                    scope.spawn(async move { update_chunk(idx * chunk_size, chunk) });
+                }
+            });
+        }
+        None => update_chunk(0, particles),
+    }
+    buffer.neighbours = scratch;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{math::Vec3, transform::components::Transform};
+
+    use super::{neighbour_pass, Neighbours};
+    use crate::{
+        templates::Flocking, ExpirationState, ParticleSeed, Projectile, ProjectileBuffer,
+        ProjectileSystem,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    struct Boid {
+        position: Vec3,
+        velocity: Vec3,
+        expired: bool,
+    }
+
+    impl Projectile for Boid {
+        fn get_transform(&self) -> Transform {
+            Transform::from_translation(self.position)
+        }
+
+        fn update(&mut self, dt: f32) {
+            self.position += self.velocity * dt;
+        }
+
+        fn expiration_state(&self) -> ExpirationState {
+            if self.expired {
+                ExpirationState::Explode
+            } else {
+                ExpirationState::None
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Flock(Flocking, f32);
+
+    impl ProjectileSystem for Flock {
+        type Projectile = Boid;
+
+        fn capacity(&self) -> usize {
+            16
+        }
+
+        fn spawn_step(&mut self, _: f32) -> usize {
+            0
+        }
+
+        fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
+            unreachable!()
+        }
+
+        fn neighbour_radius(&self) -> Option<f32> {
+            Some(self.1)
+        }
+
+        fn update_with_neighbours(
+            &self,
+            particle: &mut Boid,
+            neighbours: Neighbours<Boid>,
+            dt: f32,
+        ) {
+            particle.velocity = self.0.apply(
+                particle.position,
+                particle.velocity,
+                neighbours.iter().map(|x| (x.position, x.velocity)),
+                dt,
+            );
+        }
+    }
+
+    fn boid(position: Vec3, velocity: Vec3) -> Boid {
+        Boid {
+            position,
+            velocity,
+            expired: false,
+        }
+    }
+
+    fn simulate(system: &Flock, buffer: &mut ProjectileBuffer, steps: usize) {
+        for _ in 0..steps {
+            for particle in buffer.get_mut::<Boid>() {
+                particle.update(0.1);
+            }
+            neighbour_pass(system, 0.1, buffer, system.1);
+        }
+    }
+
+    fn spread(buffer: &ProjectileBuffer) -> f32 {
+        let particles = buffer.get::<Boid>();
+        let center = particles.iter().map(|x| x.position).sum::<Vec3>() / particles.len() as f32;
+        particles
+            .iter()
+            .map(|x| x.position.distance(center))
+            .fold(0., f32::max)
+    }
+
+    #[test]
+    fn cohesion_gathers() {
+        let system = Flock(
+            Flocking {
+                separation: 0.,
+                alignment: 0.,
+                ..Default::default()
+            },
+            10.,
+        );
+        let mut buffer = ProjectileBuffer::new_retain::<Boid>(16);
+        buffer.extend((0..8).map(|i| {
+            let angle = i as f32 * std::f32::consts::TAU / 8.;
+            boid(Vec3::new(angle.cos(), angle.sin(), 0.) * 4., Vec3::ZERO)
+        }));
+        let before = spread(&buffer);
+        simulate(&system, &mut buffer, 10);
+        assert!(spread(&buffer) < before * 0.9);
+        assert_eq!(buffer.neighbours.grid.as_ref().unwrap().cell_size(), 10.);
+    }
+
+    #[test]
+    fn separation_spreads() {
+        let system = Flock(
+            Flocking {
+                separation: 10.,
+                alignment: 0.,
+                cohesion: 0.,
+                ..Default::default()
+            },
+            2.,
+        );
+        let mut buffer = ProjectileBuffer::new_retain::<Boid>(16);
+        buffer.extend([
+            boid(Vec3::new(-0.1, 0., 0.), Vec3::ZERO),
+            boid(Vec3::new(0.1, 0., 0.), Vec3::ZERO),
+        ]);
+        simulate(&system, &mut buffer, 5);
+        let [a, b] = buffer.get::<Boid>() else {
+            panic!()
+        };
+        assert!(a.velocity.x < 0. && b.velocity.x > 0.);
+        assert!(a.position.distance(b.position) > 0.2);
+    }
+
+    #[test]
+    fn alignment_matches_velocity() {
+        let system = Flock(
+            Flocking {
+                separation: 0.,
+                cohesion: 0.,
+                ..Default::default()
+            },
+            10.,
+        );
+        let mut buffer = ProjectileBuffer::new_retain::<Boid>(16);
+        buffer.extend([
+            boid(Vec3::ZERO, Vec3::X),
+            boid(Vec3::Y, Vec3::Z),
+            Boid {
+                expired: true,
+                ..boid(Vec3::X, Vec3::NEG_X * 100.)
+            },
+        ]);
+        let before = Vec3::X.distance(Vec3::Z);
+        simulate(&system, &mut buffer, 10);
+        let [a, b, c] = buffer.get::<Boid>() else {
+            panic!()
+        };
+        assert!(a.velocity.distance(b.velocity) < before * 0.5);
+        // Expired projectiles are neither updated nor seen as neighbours.
+        assert_eq!(c.velocity, Vec3::NEG_X * 100.);
+        assert!(a.velocity.x > 0. && b.velocity.x > 0.);
+    }
+
+    #[test]
+    fn radius_change_resizes_grid() {
+        let mut system = Flock(Flocking::default(), 1.);
+        let mut buffer = ProjectileBuffer::new_retain::<Boid>(16);
+        buffer.extend([boid(Vec3::ZERO, Vec3::ZERO), boid(Vec3::X * 2., Vec3::ZERO)]);
+        simulate(&system, &mut buffer, 1);
+        assert_eq!(buffer.get::<Boid>()[0].velocity, Vec3::ZERO);
+        system.1 = 4.;
+        simulate(&system, &mut buffer, 1);
+        assert_eq!(buffer.neighbours.grid.as_ref().unwrap().cell_size(), 4.);
+        assert!(buffer.get::<Boid>()[0].velocity.x > 0.);
+    }
+}
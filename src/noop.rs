@@ -1,13 +1,13 @@
 use bevy::{color::Srgba, transform::components::Transform};
 
-use crate::{Projectile, ProjectileSystem};
+use crate::{ParticleSeed, Projectile, ProjectileSystem};
 
 #[derive(Debug, Clone, Copy)]
 pub struct NoopParticleSystem;
 
 impl Projectile for NoopParticleSystem {
-    fn get_seed(&self) -> f32 {
-        0.
+    fn get_seed(&self) -> ParticleSeed {
+        ParticleSeed::ZERO
     }
 
     fn get_lifetime(&self) -> f32 {
@@ -44,11 +44,11 @@ impl ProjectileSystem for NoopParticleSystem {
         0
     }
 
-    fn rng(&mut self) -> f32 {
-        0.
+    fn rng(&mut self, _: &mut fastrand::Rng) -> ParticleSeed {
+        ParticleSeed::ZERO
     }
 
-    fn build_particle(&self, _: f32) -> Self::Projectile {
+    fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
         NoopParticleSystem
     }
 }
@@ -0,0 +1,314 @@
+use bevy::{
+    ecs::entity::{EntityHashMap, EntityHashSet},
+    prelude::{Component, Entity, GlobalTransform, Local, Query, Res},
+};
+
+use crate::{
+    homing_target, init_particle_buffer, parent_order, report_parent_cycles, spawn_from_parent,
+    step_cluster, templates::HomingTarget, ProjectileAffectors, ProjectileBuffer, ProjectileClock,
+    ProjectileCluster, ProjectileColliders, ProjectileCollision, ProjectileEventBuffer,
+    ProjectileHitDetection, ProjectileHits, ProjectileHurtboxes, ProjectileParent,
+    ProjectileRngSeed, StepWorld,
+};
+
+/// Simulate a [`ProjectileCluster`] for some time in fixed steps when its buffer is initialized,
+/// so looping effects start in their steady state.
+///
+/// To populate an effect hierarchy, add this to the parent and its
+/// [`SubProjectileSystem`](crate::SubProjectileSystem) and
+/// [`EventProjectileSystem`](crate::EventProjectileSystem) children,
+/// children are spawned from their parents after each step.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct ProjectilePrewarm {
+    /// Amount of time to simulate.
+    pub duration: f32,
+    /// Length of each step.
+    pub step: f32,
+}
+
+impl ProjectilePrewarm {
+    /// Prewarm for `duration` seconds in steps of `1/30` seconds.
+    pub const fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            step: 1. / 30.,
+        }
+    }
+
+    /// Set the length of each step.
+    pub const fn with_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Returns the number of steps to simulate.
+    pub fn steps(&self) -> usize {
+        if self.step <= 0. {
+            return 0;
+        }
+        (self.duration / self.step).ceil() as usize
+    }
+}
+
+/// Prewarms [`ProjectileCluster`]s with [`ProjectilePrewarm`] and uninitialized buffers.
+///
+/// Steps are simulated like in [`projectile_simulation_system`](crate::projectile_simulation_system),
+/// but events and hits are discarded.
+///
+/// Children are only spawned from parents prewarmed in the same pass,
+/// other parents are left to [`projectile_simulation_system`](crate::projectile_simulation_system).
+/// Clusters with a paused [`ProjectileClock`] are not prewarmed.
+pub fn prewarm_projectiles(
+    mut reported: Local<EntityHashSet>,
+    colliders: Res<ProjectileColliders>,
+    affectors: Res<ProjectileAffectors>,
+    hurtboxes: Res<ProjectileHurtboxes>,
+    targets: Query<(&GlobalTransform, Option<&HomingTarget>)>,
+    mut particles: Query<(
+        Entity,
+        &mut ProjectileCluster,
+        &mut ProjectileBuffer,
+        &GlobalTransform,
+        Option<&mut ProjectileEventBuffer>,
+        Option<&ProjectileParent>,
+        Option<&ProjectilePrewarm>,
+        Option<&ProjectileClock>,
+        Option<&ProjectileRngSeed>,
+        Option<&ProjectileCollision>,
+        Option<(&ProjectileHitDetection, &mut ProjectileHits)>,
+    )>,
+) {
+    let mut prewarm = Vec::new();
+    for (entity, mut system, mut buffer, transform, _, _, config, clock, seed, collision, hit) in
+        &mut particles
+    {
+        let Some(config) = config else {
+            continue;
+        };
+        if !buffer.is_uninit() || clock.is_some_and(|x| x.paused) {
+            continue;
+        }
+        init_particle_buffer(&system, &mut buffer, entity, seed);
+        buffer.track_previous(collision.is_some() || hit.is_some());
+        if system.is_world_space() {
+            system.update_position(transform);
+        }
+        prewarm.push((entity, config.steps(), config.step));
+    }
+    let Some(max_steps) = prewarm.iter().map(|(_, steps, _)| *steps).max() else {
+        return;
+    };
+    let configs: EntityHashMap<_> = prewarm
+        .iter()
+        .map(|(entity, steps, dt)| (*entity, (*steps, *dt)))
+        .collect();
+    let (order, cycles) = parent_order(prewarm.iter().filter_map(|(entity, _, _)| {
+        let (_, _, _, _, _, parent, _, _, _, _, _) = particles.get(*entity).ok()?;
+        let parent = parent?.0;
+        configs.contains_key(&parent).then_some((*entity, parent))
+    }));
+    report_parent_cycles(&mut reported, cycles);
+    let targets = |entity| homing_target(&targets, entity);
+    let world = StepWorld {
+        colliders: &colliders.0,
+        affectors: &affectors.0,
+        hurtboxes: &hurtboxes.0,
+        targets: &targets,
+    };
+    for step in 0..max_steps {
+        for (entity, steps, dt) in &prewarm {
+            if step >= *steps {
+                continue;
+            }
+            let Ok((
+                _,
+                mut system,
+                mut buffer,
+                transform,
+                mut events,
+                _,
+                _,
+                _,
+                _,
+                collision,
+                mut hit,
+            )) = particles.get_mut(*entity)
+            else {
+                continue;
+            };
+            if let Some(events) = events.as_deref_mut() {
+                events.clear();
+            }
+            if let Some((_, hits)) = &mut hit {
+                hits.0.clear();
+            }
+            step_cluster(
+                *entity,
+                &mut system,
+                &mut buffer,
+                transform,
+                *dt,
+                &world,
+                collision,
+                hit.as_mut()
+                    .map(|(detection, hits)| (*detection, &mut **hits)),
+                events.as_deref_mut(),
+            );
+        }
+        for (entity, parent) in &order {
+            let Some((steps, dt)) = configs.get(entity) else {
+                continue;
+            };
+            // The parent's buffer and events are stale after its last step.
+            let parent_steps = configs.get(parent);
+            if step >= *steps || parent_steps.is_none_or(|(steps, _)| step >= *steps) {
+                continue;
+            }
+            // Safety: entities in `order` are not part of a cycle, so `entity != parent`.
+            let (
+                Ok((_, mut system, mut buffer, _, _, _, _, _, _, _, _)),
+                Ok((_, _, mut parent, _, events, _, _, _, _, _, _)),
+            ) = (unsafe {
+                (
+                    particles.get_unchecked(*entity),
+                    particles.get_unchecked(*parent),
+                )
+            })
+            else {
+                continue;
+            };
+            if parent.is_uninit() {
+                continue;
+            }
+            spawn_from_parent(
+                &mut system,
+                &mut buffer,
+                *dt,
+                &mut parent,
+                events.as_deref(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        app::{App, Update},
+        prelude::{Entity, GlobalTransform},
+        time::{Time, TimePlugin, TimeUpdateStrategy, Virtual},
+        transform::components::Transform,
+    };
+
+    use super::{prewarm_projectiles, ProjectilePrewarm};
+    use crate::{
+        projectile_simulation_system, ExpirationState, ParticleSeed, Projectile,
+        ProjectileAffectors, ProjectileBuffer, ProjectileCluster, ProjectileColliders,
+        ProjectileHurtboxes, ProjectileSystem, ProjectileTimestep,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    struct Aged(f32);
+
+    impl Projectile for Aged {
+        fn get_lifetime(&self) -> f32 {
+            self.0
+        }
+
+        fn get_transform(&self) -> Transform {
+            Transform::from_xyz(self.0, 0., 0.)
+        }
+
+        fn update(&mut self, dt: f32) {
+            self.0 += dt;
+        }
+
+        fn expiration_state(&self) -> ExpirationState {
+            ExpirationState::fizzle_if(self.0 > 0.5)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Fountain;
+
+    impl ProjectileSystem for Fountain {
+        type Projectile = Aged;
+
+        fn capacity(&self) -> usize {
+            16
+        }
+
+        fn spawn_step(&mut self, dt: f32) -> usize {
+            if dt > 0. {
+                2
+            } else {
+                0
+            }
+        }
+
+        fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
+            Aged(0.)
+        }
+    }
+
+    fn ages(app: &App, entity: Entity) -> Vec<f32> {
+        let buffer = app.world().get::<ProjectileBuffer>(entity).unwrap();
+        let mut ages: Vec<_> = buffer
+            .get::<Aged>()
+            .iter()
+            .filter(|x| !x.should_despawn())
+            .map(|x| x.0)
+            .collect();
+        ages.sort_by(f32::total_cmp);
+        ages
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ProjectileTimestep>();
+        app.init_resource::<ProjectileColliders>();
+        app.init_resource::<ProjectileAffectors>();
+        app.init_resource::<ProjectileHurtboxes>();
+        app
+    }
+
+    #[test]
+    fn prewarm_matches_simulation() {
+        let mut prewarmed = app();
+        prewarmed.add_systems(Update, prewarm_projectiles);
+        let a = prewarmed
+            .world_mut()
+            .spawn((
+                ProjectileCluster::new(Fountain),
+                ProjectileBuffer::default(),
+                GlobalTransform::IDENTITY,
+                ProjectilePrewarm::new(1.).with_step(0.125),
+            ))
+            .id();
+        prewarmed.update();
+
+        let mut simulated = app();
+        simulated.add_plugins(TimePlugin);
+        simulated.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            125,
+        )));
+        simulated.add_systems(Update, projectile_simulation_system);
+        let b = simulated
+            .world_mut()
+            .spawn((
+                ProjectileCluster::new(Fountain),
+                ProjectileBuffer::default(),
+                GlobalTransform::IDENTITY,
+            ))
+            .id();
+        while simulated.world().resource::<Time<Virtual>>().elapsed_secs() < 1. {
+            simulated.update();
+        }
+
+        let expected = ages(&simulated, b);
+        assert!(!expected.is_empty());
+        assert_eq!(ages(&prewarmed, a), expected);
+    }
+}
@@ -0,0 +1,518 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use bevy::{
+    color::{ColorToComponents, Srgba},
+    log::error,
+    math::{Mat4, Vec3, Vec4},
+    prelude::{Component, Query},
+    transform::components::Transform,
+};
+
+use crate::{
+    DefaultInstanceBuffer, ExpirationState, ParticleBufferStrategy, ParticleSeed, Projectile,
+    ProjectileBuffer, ProjectileCluster, ProjectileEvent, ProjectileEventBuffer,
+    ProjectileEventType, ProjectileInstanceBuffer, ProjectileSystem,
+};
+
+/// Start of a recording file.
+const MAGIC: &[u8; 8] = b"BERDREC\0";
+/// Version of the recording format.
+const VERSION: u32 = 1;
+
+/// State of a cluster in a single frame of a [`ProjectileRecording`].
+#[derive(Debug, Clone, Default)]
+pub struct RecordedFrame {
+    /// Time simulated in the frame.
+    pub delta: f32,
+    /// Extracted data of alive particles.
+    pub instances: Vec<DefaultInstanceBuffer>,
+    /// Events written in the frame.
+    pub events: Vec<ProjectileEvent>,
+}
+
+/// Frames recorded by a [`ProjectileRecorder`].
+#[derive(Debug, Clone, Default)]
+pub struct ProjectileRecording {
+    /// Recorded frames in order.
+    pub frames: Vec<RecordedFrame>,
+}
+
+fn event_type_to_u8(event: ProjectileEventType) -> u8 {
+    match event {
+        ProjectileEventType::Explode => 0,
+        ProjectileEventType::FadeOut => 1,
+        ProjectileEventType::Collide => 2,
+        ProjectileEventType::TargetLost => 3,
+        ProjectileEventType::TargetReached => 4,
+    }
+}
+
+fn event_type_from_u8(value: u8) -> io::Result<ProjectileEventType> {
+    Ok(match value {
+        0 => ProjectileEventType::Explode,
+        1 => ProjectileEventType::FadeOut,
+        2 => ProjectileEventType::Collide,
+        3 => ProjectileEventType::TargetLost,
+        4 => ProjectileEventType::TargetReached,
+        _ => return Err(invalid_data("Unknown event type.")),
+    })
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    read_array(reader).map(u32::from_le_bytes)
+}
+
+fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+    read_array(reader).map(f32::from_le_bytes)
+}
+
+fn read_vec3(reader: &mut impl Read) -> io::Result<Vec3> {
+    Ok(Vec3::new(
+        read_f32(reader)?,
+        read_f32(reader)?,
+        read_f32(reader)?,
+    ))
+}
+
+fn write_vec3(writer: &mut impl Write, value: Vec3) -> io::Result<()> {
+    value
+        .to_array()
+        .iter()
+        .try_for_each(|x| writer.write_all(&x.to_le_bytes()))
+}
+
+impl RecordedFrame {
+    /// Write the frame in the recording format.
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&self.delta.to_le_bytes())?;
+        writer.write_all(&(self.instances.len() as u32).to_le_bytes())?;
+        writer.write_all(bytemuck::cast_slice(&self.instances))?;
+        writer.write_all(&(self.events.len() as u32).to_le_bytes())?;
+        for event in &self.events {
+            writer.write_all(&[event_type_to_u8(event.event)])?;
+            writer.write_all(&event.seed.0.to_le_bytes())?;
+            writer.write_all(&event.index.to_le_bytes())?;
+            writer.write_all(&event.lifetime.to_le_bytes())?;
+            write_vec3(writer, event.position)?;
+            write_vec3(writer, event.tangent)?;
+            write_vec3(writer, event.normal)?;
+        }
+        Ok(())
+    }
+
+    /// Read a frame in the recording format, returns `None` at the end of input
+    /// and [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) if the frame is truncated.
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut delta = [0; 4];
+        // Only a frame that has not started is a clean end of input.
+        let mut read = 0;
+        while read < delta.len() {
+            match reader.read(&mut delta[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        let delta = f32::from_le_bytes(delta);
+        let len = read_u32(reader)? as usize;
+        // `len` is untrusted, only allocate for instances actually read.
+        let mut instances = Vec::with_capacity(len.min(4096));
+        for _ in 0..len {
+            let bytes = read_array::<{ size_of::<DefaultInstanceBuffer>() }>(reader)?;
+            instances.push(bytemuck::pod_read_unaligned(&bytes));
+        }
+        let len = read_u32(reader)? as usize;
+        let mut events = Vec::with_capacity(len.min(4096));
+        for _ in 0..len {
+            events.push(ProjectileEvent {
+                event: event_type_from_u8(read_array::<1>(reader)?[0])?,
+                seed: ParticleSeed(u64::from_le_bytes(read_array(reader)?)),
+                index: read_u32(reader)?,
+                lifetime: read_f32(reader)?,
+                position: read_vec3(reader)?,
+                tangent: read_vec3(reader)?,
+                normal: read_vec3(reader)?,
+            })
+        }
+        Ok(Some(RecordedFrame {
+            delta,
+            instances,
+            events,
+        }))
+    }
+}
+
+impl ProjectileRecording {
+    /// Read a recording written by [`ProjectileRecorder`].
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        if &read_array::<8>(reader)? != MAGIC {
+            return Err(invalid_data("Not a projectile recording."));
+        }
+        if read_u32(reader)? != VERSION {
+            return Err(invalid_data("Unsupported recording version."));
+        }
+        let mut frames = Vec::new();
+        while let Some(frame) = RecordedFrame::read_from(reader)? {
+            frames.push(frame);
+        }
+        Ok(ProjectileRecording { frames })
+    }
+
+    /// Load a recording from a file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Write the recording in the format of [`ProjectileRecorder`].
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        self.frames.iter().try_for_each(|x| x.write_to(writer))
+    }
+
+    /// Save the recording to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+}
+
+/// Writes the state of a [`ProjectileCluster`] to a file every frame,
+/// in [`ProjectileSet::ForwardEvents`](crate::ProjectileSet::ForwardEvents).
+///
+/// Records extracted data of alive particles as [`DefaultInstanceBuffer`]s and events if
+/// [`ProjectileEventBuffer`] is present, play back with [`ProjectileReplay`].
+/// Recording stops on the first IO error.
+#[derive(Debug, Component)]
+pub struct ProjectileRecorder {
+    writer: Option<BufWriter<File>>,
+    frame: RecordedFrame,
+    frames: usize,
+}
+
+impl ProjectileRecorder {
+    /// Create a file and start recording to it.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        Ok(Self {
+            writer: Some(writer),
+            frame: RecordedFrame::default(),
+            frames: 0,
+        })
+    }
+
+    /// Returns `true` if still recording.
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Returns the number of frames written.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Flush written frames to the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop recording and flush the file.
+    pub fn finish(&mut self) -> io::Result<()> {
+        let result = self.flush();
+        self.writer = None;
+        result
+    }
+}
+
+pub(crate) fn record_projectiles(
+    mut query: Query<(
+        &ProjectileCluster,
+        &ProjectileBuffer,
+        Option<&ProjectileEventBuffer>,
+        &mut ProjectileRecorder,
+    )>,
+) {
+    for (system, buffer, events, mut recorder) in &mut query {
+        let ProjectileRecorder {
+            writer: Some(writer),
+            frame,
+            frames,
+        } = recorder.as_mut()
+        else {
+            continue;
+        };
+        frame.delta = buffer.delta;
+        frame.instances.clear();
+        frame.events.clear();
+        if !buffer.is_uninit() {
+            system.instances(buffer, &mut frame.instances);
+        }
+        if let Some(events) = events {
+            frame.events.extend(events.iter().copied());
+        }
+        if let Err(e) = frame.write_to(writer) {
+            error!("Projectile recording stopped: {e}");
+            recorder.writer = None;
+            continue;
+        }
+        *frames += 1;
+    }
+}
+
+/// A projectile played back by [`ProjectileReplay`].
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayedProjectile(pub DefaultInstanceBuffer);
+
+impl Projectile for ReplayedProjectile {
+    fn get_index(&self) -> u32 {
+        self.0.index
+    }
+
+    fn get_lifetime(&self) -> f32 {
+        self.0.lifetime
+    }
+
+    fn get_fac(&self) -> f32 {
+        self.0.fac
+    }
+
+    fn get_transform(&self) -> Transform {
+        let x = self.0;
+        Transform::from_matrix(
+            Mat4::from_cols(x.transform_x, x.transform_y, x.transform_z, Vec4::W).transpose(),
+        )
+    }
+
+    fn get_color(&self) -> Srgba {
+        Srgba::from_vec4(self.0.color)
+    }
+
+    fn update(&mut self, _: f32) {}
+
+    fn expiration_state(&self) -> ExpirationState {
+        ExpirationState::None
+    }
+
+    fn extract(&self) -> impl ProjectileInstanceBuffer {
+        self.0
+    }
+
+    fn extract_interpolated(&self, _: Transform) -> impl ProjectileInstanceBuffer {
+        self.0
+    }
+}
+
+/// A [`ProjectileSystem`] that plays back a [`ProjectileRecording`] through the normal rendering path.
+///
+/// Advances through recorded frames by their recorded delta times while `playing`,
+/// use [`ProjectileReplay::seek`] to scrub.
+/// Recorded particles are in the recorded cluster's space,
+/// place this cluster at the same [`Transform`] unless the recorded cluster was in world space.
+#[derive(Debug, Clone)]
+pub struct ProjectileReplay {
+    /// The recording to play back.
+    pub recording: Arc<ProjectileRecording>,
+    /// Index of the current frame.
+    pub frame: usize,
+    /// If true, advance frames as time passes.
+    pub playing: bool,
+    /// If true, restart after the last frame.
+    pub looping: bool,
+    /// Frame shown in the buffer.
+    shown: Option<usize>,
+    /// Time played since the current frame.
+    time: f32,
+}
+
+impl ProjectileReplay {
+    /// Play back a recording from the start.
+    pub fn new(recording: impl Into<Arc<ProjectileRecording>>) -> Self {
+        Self {
+            recording: recording.into(),
+            frame: 0,
+            playing: true,
+            looping: false,
+            shown: None,
+            time: 0.,
+        }
+    }
+
+    /// Restart after the last frame.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Show a frame, clamped to the recording.
+    pub fn seek(&mut self, frame: usize) {
+        self.frame = frame.min(self.recording.frames.len().saturating_sub(1));
+        self.time = 0.;
+    }
+
+    /// Obtain the current frame.
+    pub fn current(&self) -> Option<&RecordedFrame> {
+        self.recording.frames.get(self.frame)
+    }
+}
+
+impl ProjectileSystem for ProjectileReplay {
+    type Projectile = ReplayedProjectile;
+
+    const STRATEGY: ParticleBufferStrategy = ParticleBufferStrategy::Growable { max: None };
+
+    fn capacity(&self) -> usize {
+        self.recording
+            .frames
+            .iter()
+            .map(|x| x.instances.len())
+            .max()
+            .unwrap_or(0)
+            .max(1)
+    }
+
+    fn spawn_step(&mut self, _: f32) -> usize {
+        0
+    }
+
+    fn build_particle(&self, _: ParticleSeed) -> Self::Projectile {
+        unreachable!()
+    }
+
+    fn on_update(&mut self, dt: f32, buffer: &mut ProjectileBuffer) {
+        let len = self.recording.frames.len();
+        if len == 0 {
+            return;
+        }
+        self.frame = self.frame.min(len - 1);
+        if self.playing && self.shown == Some(self.frame) {
+            self.time += dt;
+            // Frame `i + 1` is shown `frames[i + 1].delta` after frame `i`,
+            // visit each frame at most once per step.
+            for _ in 0..len {
+                let next = match self.frame + 1 {
+                    next if next < len => next,
+                    _ if self.looping => 0,
+                    _ => {
+                        self.time = 0.;
+                        break;
+                    }
+                };
+                let delta = self.recording.frames[next].delta;
+                if self.time < delta {
+                    break;
+                }
+                self.time -= delta;
+                self.frame = next;
+            }
+        }
+        if self.shown == Some(self.frame) {
+            return;
+        }
+        self.shown = Some(self.frame);
+        buffer.clear();
+        buffer.extend(
+            self.recording.frames[self.frame]
+                .instances
+                .iter()
+                .map(|x| ReplayedProjectile(*x)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, ErrorKind};
+
+    use bytemuck::Zeroable;
+
+    use super::{ProjectileRecording, ProjectileReplay, RecordedFrame, ReplayedProjectile};
+    use crate::{DefaultInstanceBuffer, ProjectileBuffer, ProjectileSystem};
+
+    fn frame(delta: f32, index: u32) -> RecordedFrame {
+        let mut instance = DefaultInstanceBuffer::zeroed();
+        instance.index = index;
+        RecordedFrame {
+            delta,
+            instances: vec![instance],
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let recording = ProjectileRecording {
+            frames: vec![frame(0.5, 1), frame(0.25, 2)],
+        };
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+        let read = ProjectileRecording::read_from(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(read.frames.len(), 2);
+        assert_eq!(read.frames[1].delta, 0.25);
+        assert_eq!(read.frames[1].instances[0].index, 2);
+    }
+
+    #[test]
+    fn untrusted_len() {
+        let mut bytes = Vec::new();
+        bytes.extend(0.5f32.to_le_bytes());
+        bytes.extend(u32::MAX.to_le_bytes());
+        assert!(RecordedFrame::read_from(&mut Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn truncated_frame() {
+        let mut bytes = Vec::new();
+        frame(0.5, 1).write_to(&mut bytes).unwrap();
+        assert!(RecordedFrame::read_from(&mut Cursor::new(&bytes[..0]))
+            .unwrap()
+            .is_none());
+        for len in [1, 3, 4, bytes.len() - 1] {
+            let error = RecordedFrame::read_from(&mut Cursor::new(&bytes[..len])).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        }
+        let mut reader = Cursor::new(&bytes);
+        assert!(RecordedFrame::read_from(&mut reader).unwrap().is_some());
+        assert!(RecordedFrame::read_from(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn replay_by_delta() {
+        let recording = ProjectileRecording {
+            frames: vec![frame(0., 0), frame(0.5, 1), frame(0.25, 2), frame(0.25, 3)],
+        };
+        let mut replay = ProjectileReplay::new(recording);
+        let mut buffer = ProjectileBuffer::new_retain::<ReplayedProjectile>(1);
+        let mut step = |replay: &mut ProjectileReplay, dt: f32| {
+            replay.on_update(dt, &mut buffer);
+            replay.frame
+        };
+        assert_eq!(step(&mut replay, 0.1), 0);
+        assert_eq!(step(&mut replay, 0.3), 0);
+        assert_eq!(step(&mut replay, 0.2), 1);
+        assert_eq!(step(&mut replay, 0.6), 3);
+        assert_eq!(step(&mut replay, 1.), 3);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{ParticleSeed, ProjectileBuffer};
+
+/// A projectile spawned on a recording [`ProjectileBuffer`],
+/// see [`ProjectileBuffer::record_spawns`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpawnRecord {
+    /// Time simulated since recording started.
+    pub time: f32,
+    /// Seed the projectile was built from.
+    pub seed: ParticleSeed,
+    /// Index of the parent particle in the parent's buffer for
+    /// [`SubProjectileSystem`](crate::SubProjectileSystem)s,
+    /// index of the event in the parent's [`ProjectileEventBuffer`](crate::ProjectileEventBuffer)
+    /// for [`EventProjectileSystem`](crate::EventProjectileSystem)s,
+    /// `None` if spawned by [`ProjectileSystem::spawn_step`](crate::ProjectileSystem::spawn_step).
+    pub parent: Option<u32>,
+}
+
+/// Spawn recording or playback state of a [`ProjectileBuffer`].
+#[derive(Debug, Clone)]
+pub(crate) enum SpawnReplication {
+    Record {
+        time: f32,
+        records: Vec<SpawnRecord>,
+    },
+    Playback {
+        time: f32,
+        pending: Vec<SpawnRecord>,
+    },
+}
+
+impl SpawnReplication {
+    fn time_mut(&mut self) -> &mut f32 {
+        match self {
+            SpawnReplication::Record { time, .. } => time,
+            SpawnReplication::Playback { time, .. } => time,
+        }
+    }
+}
+
+impl ProjectileBuffer {
+    /// Start recording spawns as [`SpawnRecord`]s, discards previous records.
+    ///
+    /// Only spawns via the cluster's random number stream are recorded,
+    /// which is how [`ProjectileSystem`](crate::ProjectileSystem),
+    /// [`SubProjectileSystem`](crate::SubProjectileSystem) and
+    /// [`EventProjectileSystem`](crate::EventProjectileSystem) spawn projectiles.
+    pub fn record_spawns(&mut self) {
+        self.replication = Some(SpawnReplication::Record {
+            time: 0.,
+            records: Vec::new(),
+        })
+    }
+
+    /// Start playing back [`SpawnRecord`]s from another buffer, added via [`ProjectileBuffer::push_spawns`].
+    ///
+    /// Local spawning is disabled, recorded projectiles are spawned when their time is reached,
+    /// or spawned and advanced by the time passed if received late.
+    ///
+    /// For projectiles to match, the [`ProjectileSystem`](crate::ProjectileSystem) must build projectiles
+    /// purely from their seed, and sub systems must have matching parents.
+    pub fn play_spawns(&mut self) {
+        self.replication = Some(SpawnReplication::Playback {
+            time: 0.,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Stop recording or playing back spawns.
+    pub fn stop_replication(&mut self) {
+        self.replication = None;
+    }
+
+    /// Returns `true` if playing back spawns.
+    pub fn is_playing_spawns(&self) -> bool {
+        matches!(self.replication, Some(SpawnReplication::Playback { .. }))
+    }
+
+    /// Take [`SpawnRecord`]s recorded since the last call.
+    pub fn take_spawns(&mut self) -> Vec<SpawnRecord> {
+        match &mut self.replication {
+            Some(SpawnReplication::Record { records, .. }) => std::mem::take(records),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Add [`SpawnRecord`]s to play back, ignored if not playing back.
+    pub fn push_spawns(&mut self, spawns: impl IntoIterator<Item = SpawnRecord>) {
+        if let Some(SpawnReplication::Playback { pending, .. }) = &mut self.replication {
+            pending.extend(spawns);
+        }
+    }
+
+    /// Advance the clock of recording or playback.
+    pub(crate) fn advance_replication(&mut self, dt: f32) {
+        if let Some(replication) = &mut self.replication {
+            *replication.time_mut() += dt;
+        }
+    }
+
+    /// Record a spawned projectile if recording.
+    pub(crate) fn record_spawn(&mut self, seed: ParticleSeed, parent: Option<u32>) {
+        if let Some(SpawnReplication::Record { time, records }) = &mut self.replication {
+            records.push(SpawnRecord {
+                time: *time,
+                seed,
+                parent,
+            })
+        }
+    }
+
+    /// Remove pending records that are due, with or without a parent,
+    /// returns seeds, parents and the time passed since their spawn.
+    pub(crate) fn due_spawns(
+        &mut self,
+        with_parent: bool,
+    ) -> Vec<(ParticleSeed, Option<u32>, f32)> {
+        let Some(SpawnReplication::Playback { time, pending }) = &mut self.replication else {
+            return Vec::new();
+        };
+        let now = *time;
+        let mut result = Vec::new();
+        pending.retain(|record| {
+            if record.time > now || record.parent.is_some() != with_parent {
+                return true;
+            }
+            result.push((record.seed, record.parent, now - record.time));
+            false
+        });
+        result
+    }
+}
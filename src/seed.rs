@@ -0,0 +1,51 @@
+/// Seed of a particle, a 64 bit random value.
+///
+/// Use [`ParticleSeed::rng`] to generate values from the seed
+/// and [`ParticleSeed::derive`] to split it into independent streams.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(transparent)]
+pub struct ParticleSeed(pub u64);
+
+/// The finalizer of `SplitMix64`.
+const fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+impl ParticleSeed {
+    pub const ZERO: Self = ParticleSeed(0);
+
+    /// Create a seed from a random number generator.
+    pub fn from_rng(rng: &mut fastrand::Rng) -> Self {
+        ParticleSeed(rng.u64(..))
+    }
+
+    /// Derive an independent seed, different `stream`s produce unrelated seeds.
+    pub const fn derive(self, stream: u64) -> Self {
+        ParticleSeed(mix(self.0 ^ mix(stream.wrapping_add(0x9e3779b97f4a7c15))))
+    }
+
+    /// Create a random number generator from this seed.
+    pub fn rng(self) -> fastrand::Rng {
+        fastrand::Rng::with_seed(self.0)
+    }
+
+    /// Convert to a normalized `0.0..1.0` value, i.e. for use in shaders.
+    pub fn as_f32(self) -> f32 {
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl From<u64> for ParticleSeed {
+    fn from(value: u64) -> Self {
+        ParticleSeed(value)
+    }
+}
+
+impl From<ParticleSeed> for u64 {
+    fn from(value: ParticleSeed) -> Self {
+        value.0
+    }
+}
@@ -0,0 +1,163 @@
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use crate::{
+    buffer::{Growth, Tracking},
+    replication::SpawnReplication,
+    Align16MaybeUninit, ErasedParticleSystem, OverflowPolicy, ParticleBufferType, ProjectileBuffer,
+    ProjectileCluster, ProjectileSystem,
+};
+
+/// A [`ProjectileSystem`] whose emitter state can be saved and restored,
+/// enables [`ClusterSnapshot`] to capture more than the [`ProjectileBuffer`].
+///
+/// Enable via [`ProjectileSystem::as_snapshot_system`].
+pub trait SnapshotProjectileSystem: ProjectileSystem {
+    /// Emitter state, i.e. spawn timers and counters.
+    type State: Clone + Send + Sync + 'static;
+
+    /// Save the emitter state.
+    fn save_state(&self) -> Self::State;
+
+    /// Restore the emitter state.
+    fn load_state(&mut self, state: &Self::State);
+}
+
+/// Type erased version of [`SnapshotProjectileSystem`].
+pub trait ErasedSnapshotProjectileSystem: ErasedParticleSystem {
+    /// Save the emitter state.
+    fn save_erased(&self) -> Arc<dyn Any + Send + Sync>;
+    /// Restore the emitter state, returns `false` if the type mismatches.
+    fn load_erased(&mut self, state: &dyn Any) -> bool;
+}
+
+impl<T> ErasedSnapshotProjectileSystem for T
+where
+    T: SnapshotProjectileSystem + ErasedParticleSystem,
+{
+    fn save_erased(&self) -> Arc<dyn Any + Send + Sync> {
+        Arc::new(self.save_state())
+    }
+
+    fn load_erased(&mut self, state: &dyn Any) -> bool {
+        match state.downcast_ref::<T::State>() {
+            Some(state) => {
+                self.load_state(state);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Saved state of a [`ProjectileBuffer`], excluding render world allocations.
+#[derive(Debug, Clone)]
+pub struct BufferSnapshot {
+    particle_type: ParticleBufferType,
+    buffer: Arc<[Align16MaybeUninit]>,
+    len: usize,
+    capacity: usize,
+    ptr: usize,
+    ring_capacity: usize,
+    tracking: Tracking,
+    accumulated: f32,
+    interpolation: Option<f32>,
+    delta: f32,
+    rng: u64,
+    growth: Option<Growth>,
+    overflow: OverflowPolicy,
+    dropped: usize,
+    replication: Option<SpawnReplication>,
+}
+
+impl ProjectileBuffer {
+    /// Save the particles and bookkeeping of this buffer.
+    pub fn snapshot(&self) -> BufferSnapshot {
+        BufferSnapshot {
+            particle_type: self.particle_type,
+            buffer: self.buffer.iter().copied().collect(),
+            len: self.len,
+            capacity: self.capacity,
+            ptr: self.ptr,
+            ring_capacity: self.ring_capacity,
+            tracking: self.tracking.clone(),
+            accumulated: self.accumulated,
+            interpolation: self.interpolation,
+            delta: self.delta,
+            rng: self.rng,
+            growth: self.growth,
+            overflow: self.overflow,
+            dropped: self.dropped,
+            replication: self.replication.clone(),
+        }
+    }
+
+    /// Restore a [`BufferSnapshot`], reuses the allocation if capacity has not changed.
+    pub fn restore(&mut self, snapshot: &BufferSnapshot) {
+        if self.buffer.len() == snapshot.buffer.len() {
+            self.buffer.copy_from_slice(&snapshot.buffer);
+        } else {
+            self.buffer = snapshot.buffer.iter().copied().collect();
+        }
+        self.particle_type = snapshot.particle_type;
+        self.len = snapshot.len;
+        self.capacity = snapshot.capacity;
+        self.ptr = snapshot.ptr;
+        self.ring_capacity = snapshot.ring_capacity;
+        self.tracking.clone_from(&snapshot.tracking);
+        self.accumulated = snapshot.accumulated;
+        self.interpolation = snapshot.interpolation;
+        self.delta = snapshot.delta;
+        self.rng = snapshot.rng;
+        self.growth = snapshot.growth;
+        self.overflow = snapshot.overflow;
+        self.dropped = snapshot.dropped;
+        self.replication.clone_from(&snapshot.replication);
+    }
+}
+
+/// Saved state of a [`ProjectileCluster`] and its [`ProjectileBuffer`] for rollback.
+///
+/// Cloning is cheap, particle data and emitter state are shared.
+/// [`ProjectileEventBuffer`](crate::ProjectileEventBuffer)s are not saved
+/// since they are rewritten every frame.
+#[derive(Debug, Clone)]
+pub struct ClusterSnapshot {
+    /// State of the buffer.
+    pub buffer: BufferSnapshot,
+    /// State of the emitter, `None` if not a [`SnapshotProjectileSystem`].
+    pub emitter: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl ProjectileCluster {
+    /// Save the state of this cluster and its buffer.
+    pub fn snapshot(&mut self, buffer: &ProjectileBuffer) -> ClusterSnapshot {
+        ClusterSnapshot {
+            buffer: buffer.snapshot(),
+            emitter: self.as_snapshot_system().map(|x| x.save_erased()),
+        }
+    }
+
+    /// Restore the state of this cluster and its buffer.
+    ///
+    /// # Panics
+    ///
+    /// If the emitter state does not belong to this cluster's [`ProjectileSystem`].
+    pub fn restore(&mut self, buffer: &mut ProjectileBuffer, snapshot: &ClusterSnapshot) {
+        buffer.restore(&snapshot.buffer);
+        let Some(state) = &snapshot.emitter else {
+            return;
+        };
+        let loaded = self
+            .as_snapshot_system()
+            .is_some_and(|system| system.load_erased(state.as_ref()));
+        if !loaded {
+            panic!("Emitter state type mismatch!")
+        }
+    }
+}
+
+impl Debug for dyn ErasedSnapshotProjectileSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_debug().fmt(f)
+    }
+}
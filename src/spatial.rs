@@ -0,0 +1,516 @@
+use bevy::{
+    ecs::system::SystemParam,
+    math::{IVec3, Ray3d, Vec3},
+    prelude::{Component, Entity, Query},
+    transform::components::GlobalTransform,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{ProjectileBuffer, ProjectileCluster};
+
+/// A uniform grid over alive projectiles of a [`ProjectileCluster`] in world space,
+/// rebuilt in [`ProjectileSet::SpatialIndex`](crate::ProjectileSet::SpatialIndex).
+///
+/// Query all indexed clusters via [`ProjectileSpatialQuery`].
+///
+/// Indices returned by queries are indices into [`ProjectileBuffer::get`] at the time of the rebuild,
+/// they change when the buffer is cleaned up in the next step.
+/// Convert them to stable handles via [`ProjectileBuffer::handle`] before then.
+#[derive(Debug, Clone, Component)]
+pub struct ProjectileSpatialIndex {
+    /// Size of each grid cell, should be around the typical query radius, always positive.
+    cell_size: f32,
+    /// Index in the particle buffer and world space position of each projectile.
+    entries: Vec<(usize, Vec3)>,
+    /// Indices into `entries` per cell.
+    cells: HashMap<IVec3, Vec<usize>>,
+    /// Bounds of finite positions in `entries`.
+    bounds: (Vec3, Vec3),
+}
+
+impl Default for ProjectileSpatialIndex {
+    fn default() -> Self {
+        Self::new(1.)
+    }
+}
+
+impl ProjectileSpatialIndex {
+    /// Create a spatial index with a cell size.
+    ///
+    /// # Panics
+    ///
+    /// If `cell_size` is not positive and finite.
+    pub fn new(cell_size: f32) -> Self {
+        validate_cell_size(cell_size);
+        Self {
+            cell_size,
+            entries: Vec::new(),
+            cells: HashMap::default(),
+            bounds: (Vec3::ZERO, Vec3::ZERO),
+        }
+    }
+
+    /// Returns the size of each grid cell.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Change the size of each grid cell, indexed projectiles are kept.
+    ///
+    /// # Panics
+    ///
+    /// If `cell_size` is not positive and finite.
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        validate_cell_size(cell_size);
+        self.cell_size = cell_size;
+        self.rebuild_cells();
+    }
+
+    fn cell(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size).floor().as_ivec3()
+    }
+
+    /// Rebuild from world space positions of projectiles.
+    pub fn rebuild(&mut self, entries: impl IntoIterator<Item = (usize, Vec3)>) {
+        self.entries.clear();
+        self.entries.extend(entries);
+        self.rebuild_cells();
+    }
+
+    fn rebuild_cells(&mut self) {
+        self.cells.values_mut().for_each(Vec::clear);
+        let mut bounds = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        for (idx, (_, position)) in self.entries.iter().enumerate() {
+            let cell = self.cell(*position);
+            self.cells.entry(cell).or_default().push(idx);
+            if position.is_finite() {
+                bounds = (bounds.0.min(*position), bounds.1.max(*position));
+            }
+        }
+        self.cells.retain(|_, x| !x.is_empty());
+        self.bounds = bounds;
+    }
+
+    /// Returns the number of indexed projectiles.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no projectile is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Call `f` with index and position of projectiles inside an axis aligned box.
+    pub fn for_each_in_aabb(&self, min: Vec3, max: Vec3, mut f: impl FnMut(usize, Vec3)) {
+        let (lo, hi) = (self.cell(min), self.cell(max));
+        let span = |a: i32, b: i32| (b as i64 - a as i64 + 1).max(0) as u64;
+        let cells = span(lo.x, hi.x)
+            .saturating_mul(span(lo.y, hi.y))
+            .saturating_mul(span(lo.z, hi.z));
+        if cells > self.cells.len() as u64 {
+            for (cell, entries) in &self.cells {
+                if cell.cmpge(lo).all() && cell.cmple(hi).all() {
+                    self.visit(entries, min, max, &mut f);
+                }
+            }
+            return;
+        }
+        for x in lo.x..=hi.x {
+            for y in lo.y..=hi.y {
+                for z in lo.z..=hi.z {
+                    if let Some(entries) = self.cells.get(&IVec3::new(x, y, z)) {
+                        self.visit(entries, min, max, &mut f);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit(&self, entries: &[usize], min: Vec3, max: Vec3, f: &mut impl FnMut(usize, Vec3)) {
+        for (index, position) in entries.iter().map(|x| self.entries[*x]) {
+            if position.cmpge(min).all() && position.cmple(max).all() {
+                f(index, position)
+            }
+        }
+    }
+
+    /// Call `f` with index and position of projectiles inside a sphere.
+    pub fn for_each_in_sphere(&self, center: Vec3, radius: f32, mut f: impl FnMut(usize, Vec3)) {
+        let r2 = radius * radius;
+        self.for_each_in_aabb(center - radius, center + radius, |index, position| {
+            if position.distance_squared(center) <= r2 {
+                f(index, position)
+            }
+        })
+    }
+
+    /// Find the first projectile within `radius` of a ray,
+    /// returns its index, position and distance along the ray.
+    ///
+    /// Walks cells along the ray until a projectile is found.
+    pub fn cast_ray(
+        &self,
+        ray: Ray3d,
+        max_distance: f32,
+        radius: f32,
+    ) -> Option<(usize, Vec3, f32)> {
+        let origin = ray.origin;
+        let dir = *ray.direction;
+        let radius = radius.max(0.);
+        // Clip the ray against bounds of all projectiles.
+        let (min, max) = (self.bounds.0 - radius, self.bounds.1 + radius);
+        let t1 = (min - origin) / dir;
+        let t2 = (max - origin) / dir;
+        let enter = t1.min(t2).max_element().max(0.);
+        let exit = t1.max(t2).min_element().min(max_distance);
+        if self.entries.is_empty() || exit.is_nan() || enter > exit {
+            return None;
+        }
+        let mut best: Option<(usize, Vec3, f32)> = None;
+        let test = |best: &mut Option<(usize, Vec3, f32)>,
+                    entries: &mut dyn Iterator<Item = (usize, Vec3)>| {
+            for (index, position) in entries {
+                let t = (position - origin).dot(dir);
+                let limit = best.map_or(max_distance, |(_, _, t)| t);
+                if t < 0. || t > limit {
+                    continue;
+                }
+                if (origin + dir * t).distance_squared(position) <= radius * radius {
+                    *best = Some((index, position, t));
+                }
+            }
+        };
+        // Cells around each cell on the ray that may contain projectiles within `radius`.
+        let reach = (radius / self.cell_size).ceil();
+        let block = (reach * 2. + 1.).powi(3);
+        let steps = ((exit - enter) / self.cell_size).ceil() * 3. + 1.;
+        let cost = block * steps;
+        if cost.is_nan() || cost >= self.entries.len() as f32 {
+            test(&mut best, &mut self.entries.iter().copied());
+            return best;
+        }
+        let reach = reach as i32;
+        // Projectiles in cells visited from a cell are this close along the ray.
+        let slack = (reach + 1) as f32 * self.cell_size * 3f32.sqrt();
+        let mut cell = self.cell(origin + dir * enter);
+        let step = dir.signum().as_ivec3();
+        let delta = (self.cell_size / dir).abs();
+        let mut next = Vec3::ZERO;
+        for axis in 0..3 {
+            next[axis] = if dir[axis] == 0. {
+                f32::INFINITY
+            } else {
+                let boundary = (cell[axis] + (step[axis] > 0) as i32) as f32 * self.cell_size;
+                (boundary - origin[axis]) / dir[axis]
+            };
+        }
+        let mut visited = HashSet::new();
+        let mut t = enter;
+        while t <= exit {
+            for x in -reach..=reach {
+                for y in -reach..=reach {
+                    for z in -reach..=reach {
+                        let neighbour = cell + IVec3::new(x, y, z);
+                        let Some(entries) = self.cells.get(&neighbour) else {
+                            continue;
+                        };
+                        if visited.insert(neighbour) {
+                            test(&mut best, &mut entries.iter().map(|x| self.entries[*x]));
+                        }
+                    }
+                }
+            }
+            if best.is_some_and(|(_, _, best)| best < t - slack) {
+                break;
+            }
+            let axis = if next.x <= next.y && next.x <= next.z {
+                0
+            } else if next.y <= next.z {
+                1
+            } else {
+                2
+            };
+            t = next[axis];
+            cell[axis] += step[axis];
+            next[axis] += delta[axis];
+        }
+        best
+    }
+
+    /// Call `f` with index and position of up to `k` projectiles closest to a point,
+    /// in order of distance.
+    pub fn nearest(&self, point: Vec3, k: usize, mut f: impl FnMut(usize, Vec3)) {
+        if k == 0 || self.entries.is_empty() {
+            return;
+        }
+        // Once the sphere contains all finite positions, growing it further cannot help.
+        let (min, max) = self.bounds;
+        let extent = (point - min).abs().max((point - max).abs()).length();
+        let mut found = Vec::new();
+        let mut radius = self.cell_size;
+        loop {
+            found.clear();
+            if !(radius > 0. && radius <= extent && radius.is_finite()) {
+                found.extend(self.entries.iter().copied());
+                break;
+            }
+            self.for_each_in_sphere(point, radius, |index, position| {
+                found.push((index, position))
+            });
+            if found.len() >= k.min(self.entries.len()) {
+                break;
+            }
+            radius *= 2.;
+        }
+        found.sort_by(|(_, a), (_, b)| {
+            a.distance_squared(point)
+                .total_cmp(&b.distance_squared(point))
+        });
+        found
+            .into_iter()
+            .take(k)
+            .for_each(|(index, position)| f(index, position));
+    }
+}
+
+fn validate_cell_size(cell_size: f32) {
+    assert!(
+        cell_size > 0. && cell_size.is_finite(),
+        "Cell size must be positive and finite."
+    );
+}
+
+/// Rebuild [`ProjectileSpatialIndex`]es from alive projectiles.
+pub(crate) fn build_spatial_index(
+    mut query: Query<(
+        &ProjectileCluster,
+        &ProjectileBuffer,
+        &GlobalTransform,
+        &mut ProjectileSpatialIndex,
+    )>,
+) {
+    query
+        .par_iter_mut()
+        .for_each(|(system, buffer, transform, mut index)| {
+            if buffer.is_uninit() {
+                index.rebuild([]);
+                return;
+            }
+            let mut entries = Vec::new();
+            system.positions(buffer, &mut entries);
+            if !system.is_world_space() {
+                entries
+                    .iter_mut()
+                    .for_each(|(_, x)| *x = transform.transform_point(*x));
+            }
+            index.rebuild(entries);
+        });
+}
+
+/// A projectile found by [`ProjectileSpatialQuery`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProjectileQueryItem {
+    /// Entity of the [`ProjectileCluster`].
+    pub cluster: Entity,
+    /// Index of the projectile in [`ProjectileBuffer::get`], changes on the next cleanup,
+    /// convert to a stable handle via [`ProjectileBuffer::handle`].
+    pub index: usize,
+    /// World space position of the projectile.
+    pub position: Vec3,
+}
+
+/// [`SystemParam`] for spatial queries over clusters with [`ProjectileSpatialIndex`].
+///
+/// Results reflect positions at [`ProjectileSet::SpatialIndex`](crate::ProjectileSet::SpatialIndex).
+#[derive(SystemParam)]
+pub struct ProjectileSpatialQuery<'w, 's> {
+    indices: Query<'w, 's, (Entity, &'static ProjectileSpatialIndex)>,
+}
+
+impl ProjectileSpatialQuery<'_, '_> {
+    /// Find projectiles inside a sphere.
+    pub fn within_sphere(&self, center: Vec3, radius: f32) -> Vec<ProjectileQueryItem> {
+        let mut result = Vec::new();
+        for (cluster, index) in &self.indices {
+            index.for_each_in_sphere(center, radius, |index, position| {
+                result.push(ProjectileQueryItem {
+                    cluster,
+                    index,
+                    position,
+                })
+            });
+        }
+        result
+    }
+
+    /// Find projectiles inside an axis aligned box.
+    pub fn within_aabb(&self, min: Vec3, max: Vec3) -> Vec<ProjectileQueryItem> {
+        let mut result = Vec::new();
+        for (cluster, index) in &self.indices {
+            index.for_each_in_aabb(min, max, |index, position| {
+                result.push(ProjectileQueryItem {
+                    cluster,
+                    index,
+                    position,
+                })
+            });
+        }
+        result
+    }
+
+    /// Find the first projectile within `radius` of a ray and its distance along the ray.
+    pub fn cast_ray(
+        &self,
+        ray: Ray3d,
+        max_distance: f32,
+        radius: f32,
+    ) -> Option<(ProjectileQueryItem, f32)> {
+        self.indices
+            .iter()
+            .filter_map(|(cluster, index)| {
+                let (index, position, t) = index.cast_ray(ray, max_distance, radius)?;
+                Some((
+                    ProjectileQueryItem {
+                        cluster,
+                        index,
+                        position,
+                    },
+                    t,
+                ))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// Find up to `k` projectiles closest to a point, in order of distance.
+    pub fn nearest(&self, point: Vec3, k: usize) -> Vec<ProjectileQueryItem> {
+        let mut result = Vec::new();
+        for (cluster, index) in &self.indices {
+            index.nearest(point, k, |index, position| {
+                result.push(ProjectileQueryItem {
+                    cluster,
+                    index,
+                    position,
+                })
+            });
+        }
+        result.sort_by(|a, b| {
+            a.position
+                .distance_squared(point)
+                .total_cmp(&b.position.distance_squared(point))
+        });
+        result.truncate(k);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::{Dir3, Ray3d, Vec3};
+
+    use super::ProjectileSpatialIndex;
+
+    fn index() -> ProjectileSpatialIndex {
+        let mut index = ProjectileSpatialIndex::new(1.);
+        index.rebuild([
+            (0, Vec3::ZERO),
+            (1, Vec3::new(3., 0., 0.)),
+            (2, Vec3::new(0., -10., 2.)),
+        ]);
+        index
+    }
+
+    fn nearest(index: &ProjectileSpatialIndex, point: Vec3, k: usize) -> Vec<usize> {
+        let mut result = Vec::new();
+        index.nearest(point, k, |index, _| result.push(index));
+        result
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_cell_size() {
+        ProjectileSpatialIndex::new(0.);
+    }
+
+    #[test]
+    fn huge_aabb() {
+        let mut count = 0;
+        index().for_each_in_aabb(Vec3::splat(-1e30), Vec3::splat(1e30), |_, _| count += 1);
+        assert_eq!(count, 3);
+        let mut count = 0;
+        index().for_each_in_aabb(Vec3::NEG_INFINITY, Vec3::INFINITY, |_, _| count += 1);
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn nearest_in_order() {
+        assert_eq!(nearest(&index(), Vec3::new(2., 0., 0.), 2), [1, 0]);
+        assert_eq!(nearest(&index(), Vec3::new(2., 0., 0.), 5), [1, 0, 2]);
+    }
+
+    #[test]
+    fn nearest_terminates() {
+        assert_eq!(nearest(&index(), Vec3::new(1e6, 0., 0.), 1), [1]);
+        assert_eq!(nearest(&index(), Vec3::NAN, 3).len(), 3);
+        let mut index = index();
+        index.rebuild([(0, Vec3::ZERO), (1, Vec3::NAN), (2, Vec3::INFINITY)]);
+        assert_eq!(nearest(&index, Vec3::ONE, 3)[0], 0);
+        index.set_cell_size(1e-30);
+        assert_eq!(nearest(&index, Vec3::ONE, 3).len(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_zero_cell_size() {
+        index().set_cell_size(0.);
+    }
+
+    /// Returns distance along the ray of the first hit by testing every projectile.
+    fn scan(points: &[(usize, Vec3)], ray: Ray3d, max_distance: f32, radius: f32) -> Option<f32> {
+        points
+            .iter()
+            .map(|(_, p)| (*p - ray.origin).dot(*ray.direction))
+            .zip(points)
+            .filter(|(t, (_, p))| {
+                (0. ..=max_distance).contains(t) && ray.get_point(*t).distance(*p) <= radius
+            })
+            .map(|(t, _)| t)
+            .min_by(f32::total_cmp)
+    }
+
+    #[test]
+    fn cast_ray_matches_scan() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        let mut vec = |scale: f32| Vec3::new(rng.f32(), rng.f32(), rng.f32()) * scale * 2. - scale;
+        let points: Vec<_> = (0..2000).map(|i| (i, vec(10.))).collect();
+        let mut index = ProjectileSpatialIndex::new(1.);
+        index.rebuild(points.iter().copied());
+        let mut hits = 0;
+        for i in 0..300 {
+            let origin = vec(10.);
+            let dir = match i % 3 {
+                0 => vec(1.),
+                1 => Vec3::new(vec(1.).x, 0., 0.),
+                _ => Vec3::new(0., vec(1.).y, vec(1.).z),
+            };
+            let Ok(dir) = Dir3::new(dir) else {
+                continue;
+            };
+            let ray = Ray3d::new(origin, dir);
+            let (max_distance, radius) = (vec(8.).x + 8., (vec(0.5).x + 0.5) * 0.9);
+            let expected = scan(&points, ray, max_distance, radius);
+            let result = index.cast_ray(ray, max_distance, radius);
+            assert_eq!(
+                result.map(|(_, _, t)| t),
+                expected,
+                "{ray:?} {max_distance} {radius}"
+            );
+            if let Some((idx, position, t)) = result {
+                assert_eq!(points[idx].1, position);
+                assert!(ray.get_point(t).distance(position) <= radius);
+                hits += 1;
+            }
+        }
+        assert!(hits > 100, "{hits}");
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::{
+    ecs::entity::EntityHashMap,
     math::Vec3,
     prelude::{Component, Entity},
 };
@@ -6,15 +7,26 @@ use std::fmt::Debug;
 use std::ops::{Deref, DerefMut};
 
 use crate::{
-    ErasedParticleSystem, ExpirationState, Projectile, ProjectileBuffer, ProjectileSystem,
+    ErasedParticleSystem, ExpirationState, ParticleSeed, Projectile, ProjectileBuffer,
+    ProjectileSystem,
 };
 
 /// Event on individual particle.
+///
+/// More events may be added, match with a wildcard arm.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum ProjectileEventType {
     Explode,
     FadeOut,
     Collide,
+    /// Target of a [`Homing`](crate::templates::Homing) projectile is lost,
+    /// only sent if [`Homing::emit_events`](crate::templates::Homing::emit_events) is set.
+    TargetLost,
+    /// Target of a [`Homing`](crate::templates::Homing) projectile is reached,
+    /// only sent if [`Homing::emit_events`](crate::templates::Homing::emit_events) is set.
+    TargetReached,
 }
 
 impl From<ExpirationState> for ProjectileEventType {
@@ -23,6 +35,7 @@ impl From<ExpirationState> for ProjectileEventType {
             ExpirationState::None => panic!(),
             ExpirationState::FadeOut => ProjectileEventType::FadeOut,
             ExpirationState::Explode => ProjectileEventType::Explode,
+            ExpirationState::Collide => ProjectileEventType::Collide,
         }
     }
 }
@@ -31,11 +44,13 @@ impl From<ExpirationState> for ProjectileEventType {
 #[derive(Debug, Clone, Copy)]
 pub struct ProjectileEvent {
     pub event: ProjectileEventType,
-    pub seed: f32,
+    pub seed: ParticleSeed,
     pub index: u32,
     pub lifetime: f32,
     pub position: Vec3,
     pub tangent: Vec3,
+    /// Surface normal on [`ProjectileEventType::Collide`], otherwise zero.
+    pub normal: Vec3,
 }
 
 /// Parent of the particle, if present will read data/event from the parent's particle buffer.
@@ -54,6 +69,57 @@ impl From<Entity> for ProjectileParent {
     }
 }
 
+/// Sort `(child, parent)` pairs so parents are processed before their children.
+///
+/// Returns the sorted pairs and children that are part of or descend from a cycle,
+/// which are excluded from the sorted pairs.
+pub(crate) fn parent_order(
+    pairs: impl IntoIterator<Item = (Entity, Entity)>,
+) -> (Vec<(Entity, Entity)>, Vec<Entity>) {
+    let parents: EntityHashMap<Entity> = pairs.into_iter().collect();
+    // `None` if in or below a cycle.
+    let mut depths = EntityHashMap::<Option<usize>>::default();
+    let mut path = Vec::new();
+    for &child in parents.keys() {
+        let mut current = child;
+        let base = loop {
+            if let Some(depth) = depths.get(&current) {
+                break *depth;
+            }
+            if path.contains(&current) {
+                break None;
+            }
+            let Some(parent) = parents.get(&current) else {
+                break Some(0);
+            };
+            path.push(current);
+            current = *parent;
+        };
+        let mut depth = base;
+        for entity in path.drain(..).rev() {
+            depth = depth.map(|x| x + 1);
+            depths.insert(entity, depth);
+        }
+    }
+    let mut order = Vec::new();
+    let mut cycles = Vec::new();
+    for (child, parent) in parents {
+        match depths.get(&child).copied().flatten() {
+            Some(depth) => order.push((depth, child, parent)),
+            None => cycles.push(child),
+        }
+    }
+    order.sort_unstable_by_key(|(depth, child, _)| (*depth, *child));
+    cycles.sort_unstable();
+    (
+        order
+            .into_iter()
+            .map(|(_, child, parent)| (child, parent))
+            .collect(),
+        cycles,
+    )
+}
+
 /// A buffer of particle events. If added to a particle bundle, will record particle events happened
 /// in this frame. Also enables [`EventProjectileSystem`].
 #[derive(Debug, Component, Default)]
@@ -84,7 +150,7 @@ pub trait SubProjectileSystem: ProjectileSystem {
     fn spawn_step_sub(&mut self, parent: &mut Self::Parent, dt: f32) -> usize;
 
     /// Convert a random seed into a particle with parent information.
-    fn build_sub_projectile(parent: &Self::Parent, seed: f32) -> Self::Projectile;
+    fn build_sub_projectile(parent: &Self::Parent, seed: ParticleSeed) -> Self::Projectile;
 }
 
 /// An erased [`SubProjectileSystem`].
@@ -107,17 +173,37 @@ where
         buffer: &mut ProjectileBuffer,
         parent: &mut ProjectileBuffer,
     ) {
-        for parent in parent.get_mut::<T::Parent>() {
+        let parents = parent.get_mut::<T::Parent>();
+        if buffer.is_playing_spawns() {
+            for (seed, index, age) in buffer.due_spawns(true) {
+                let Some(parent) = parents.get_mut(index.unwrap_or(0) as usize) else {
+                    continue;
+                };
+                let mut particle = Self::build_sub_projectile(parent, seed);
+                if age > 0. {
+                    particle.update(age);
+                }
+                buffer.extend([particle]);
+            }
+            return;
+        }
+        let mut rng = buffer.rng();
+        for (index, parent) in parents.iter_mut().enumerate() {
             if parent.is_expired() {
                 continue;
             }
             let num = self.spawn_step_sub(parent, dt);
+            let seeds: Vec<_> = (0..num).map(|_| self.rng(&mut rng)).collect();
+            for seed in &seeds {
+                buffer.record_spawn(*seed, Some(index as u32));
+            }
             buffer.extend(
-                (0..num)
-                    .map(|_| self.rng())
+                seeds
+                    .into_iter()
                     .map(|seed| Self::build_sub_projectile(parent, seed)),
             )
         }
+        buffer.set_rng(&rng);
     }
 }
 
@@ -135,7 +221,7 @@ pub trait EventProjectileSystem: ProjectileSystem {
     fn spawn_on_event(&mut self, parent: &ProjectileEvent) -> usize;
 
     /// Convert a random seed into a particle with parent information.
-    fn build_sub_projectile(parent: &ProjectileEvent, seed: f32) -> Self::Projectile;
+    fn build_sub_projectile(parent: &ProjectileEvent, seed: ParticleSeed) -> Self::Projectile;
 }
 
 /// Type erased [`EventProjectileSystem`].
@@ -149,14 +235,33 @@ where
     T: EventProjectileSystem + ErasedParticleSystem,
 {
     fn spawn_on_event(&mut self, buffer: &mut ProjectileBuffer, parent: &ProjectileEventBuffer) {
-        for event in parent.iter() {
+        if buffer.is_playing_spawns() {
+            for (seed, index, age) in buffer.due_spawns(true) {
+                let Some(event) = parent.get(index.unwrap_or(0) as usize) else {
+                    continue;
+                };
+                let mut particle = Self::build_sub_projectile(event, seed);
+                if age > 0. {
+                    particle.update(age);
+                }
+                buffer.extend([particle]);
+            }
+            return;
+        }
+        let mut rng = buffer.rng();
+        for (index, event) in parent.iter().enumerate() {
             let num = self.spawn_on_event(event);
+            let seeds: Vec<_> = (0..num).map(|_| self.rng(&mut rng)).collect();
+            for seed in &seeds {
+                buffer.record_spawn(*seed, Some(index as u32));
+            }
             buffer.extend(
-                (0..num)
-                    .map(|_| self.rng())
+                seeds
+                    .into_iter()
                     .map(|seed| Self::build_sub_projectile(event, seed)),
             )
         }
+        buffer.set_rng(&rng);
     }
 }
 
@@ -165,3 +270,38 @@ impl Debug for dyn ErasedEventParticleSystem {
         self.as_debug().fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Entity;
+
+    use super::parent_order;
+
+    fn e(index: u32) -> Entity {
+        Entity::from_raw(index)
+    }
+
+    #[test]
+    fn chain_order() {
+        let (order, cycles) =
+            parent_order([(e(3), e(2)), (e(1), e(0)), (e(4), e(0)), (e(2), e(1))]);
+        assert_eq!(
+            order,
+            [(e(1), e(0)), (e(4), e(0)), (e(2), e(1)), (e(3), e(2))]
+        );
+        assert!(cycles.is_empty());
+    }
+
+    #[test]
+    fn cycles_reported() {
+        let (order, cycles) = parent_order([
+            (e(1), e(2)),
+            (e(2), e(1)),
+            (e(3), e(1)),
+            (e(5), e(4)),
+            (e(6), e(6)),
+        ]);
+        assert_eq!(order, [(e(5), e(4))]);
+        assert_eq!(cycles, [e(1), e(2), e(3), e(6)]);
+    }
+}
@@ -0,0 +1,99 @@
+use bevy::math::Vec3;
+
+/// Steering away from neighbours closer than `radius`, stronger when closer.
+pub fn separation(position: Vec3, neighbours: impl IntoIterator<Item = Vec3>, radius: f32) -> Vec3 {
+    neighbours
+        .into_iter()
+        .filter_map(|other| {
+            let offset = position - other;
+            let distance = offset.length();
+            if distance <= 0. || distance >= radius {
+                return None;
+            }
+            Some(offset / distance * (1. - distance / radius))
+        })
+        .sum()
+}
+
+/// Steering towards the average velocity of neighbours.
+pub fn alignment(velocity: Vec3, neighbours: impl IntoIterator<Item = Vec3>) -> Vec3 {
+    let (sum, count) = neighbours
+        .into_iter()
+        .fold((Vec3::ZERO, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        return Vec3::ZERO;
+    }
+    sum / count as f32 - velocity
+}
+
+/// Steering towards the average position of neighbours.
+pub fn cohesion(position: Vec3, neighbours: impl IntoIterator<Item = Vec3>) -> Vec3 {
+    let (sum, count) = neighbours
+        .into_iter()
+        .fold((Vec3::ZERO, 0), |(sum, count), p| (sum + p, count + 1));
+    if count == 0 {
+        return Vec3::ZERO;
+    }
+    sum / count as f32 - position
+}
+
+/// A boids style flocking template combining [`separation`], [`alignment`] and [`cohesion`].
+///
+/// Use in [`ProjectileSystem::update_with_neighbours`](crate::ProjectileSystem::update_with_neighbours).
+#[derive(Debug, Clone, Copy)]
+pub struct Flocking {
+    /// Weight of separation.
+    pub separation: f32,
+    /// Neighbours closer than this are pushed away.
+    pub separation_radius: f32,
+    /// Weight of alignment.
+    pub alignment: f32,
+    /// Weight of cohesion.
+    pub cohesion: f32,
+    /// Maximum speed after steering, unlimited if `0`.
+    pub max_speed: f32,
+}
+
+impl Default for Flocking {
+    fn default() -> Self {
+        Self {
+            separation: 1.5,
+            separation_radius: 1.,
+            alignment: 1.,
+            cohesion: 1.,
+            max_speed: 0.,
+        }
+    }
+}
+
+impl Flocking {
+    /// Obtain the steering acceleration from positions and velocities of neighbours.
+    pub fn steer(
+        &self,
+        position: Vec3,
+        velocity: Vec3,
+        neighbours: impl IntoIterator<Item = (Vec3, Vec3)> + Clone,
+    ) -> Vec3 {
+        let positions = || neighbours.clone().into_iter().map(|(p, _)| p);
+        let velocities = neighbours.clone().into_iter().map(|(_, v)| v);
+        separation(position, positions(), self.separation_radius) * self.separation
+            + alignment(velocity, velocities) * self.alignment
+            + cohesion(position, positions()) * self.cohesion
+    }
+
+    /// Apply steering to a velocity over `dt`, limited by `max_speed`.
+    pub fn apply(
+        &self,
+        position: Vec3,
+        velocity: Vec3,
+        neighbours: impl IntoIterator<Item = (Vec3, Vec3)> + Clone,
+        dt: f32,
+    ) -> Vec3 {
+        let velocity = velocity + self.steer(position, velocity, neighbours) * dt;
+        if self.max_speed > 0. {
+            velocity.clamp_length_max(self.max_speed)
+        } else {
+            velocity
+        }
+    }
+}
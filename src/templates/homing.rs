@@ -0,0 +1,320 @@
+use bevy::{
+    math::{Affine3A, Quat, Vec3},
+    prelude::{Component, Entity, Query, Res},
+    time::Time,
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::{
+    Projectile, ProjectileBuffer, ProjectileEvent, ProjectileEventBuffer, ProjectileEventType,
+};
+
+/// State of a [`Homing`] projectile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HomingState {
+    /// Steering towards the target, or flying straight if there is no target.
+    #[default]
+    Tracking,
+    /// The target entity no longer exists or has no [`GlobalTransform`].
+    Lost,
+    /// Came within [`Homing::reach_distance`] of the target.
+    Reached,
+}
+
+/// A homing projectile template that steers towards a target entity.
+///
+/// Expose via [`Projectile::as_homing_mut`], target positions are looked up from
+/// [`GlobalTransform`] before each step, add [`HomingTarget`] to the target for lead prediction.
+/// If [`Homing::emit_events`] is set, writes [`ProjectileEventType::TargetLost`] and
+/// [`ProjectileEventType::TargetReached`] to [`ProjectileEventBuffer`] when the state changes.
+///
+/// Call [`Homing::update`] in [`Projectile::update`].
+#[derive(Debug, Clone, Copy)]
+pub struct Homing {
+    /// Entity to steer towards.
+    pub target: Option<Entity>,
+    /// Position of the projectile.
+    pub position: Vec3,
+    /// Position of the projectile before the last step.
+    pub previous_position: Vec3,
+    /// Velocity of the projectile.
+    pub velocity: Vec3,
+    /// Maximum turn rate in radians per second.
+    pub turn_rate: f32,
+    /// Speed relative to time since launch.
+    pub speed: fn(f32) -> f32,
+    /// Lead prediction, `0` aims at the target, `1` aims at the predicted intercept point.
+    pub lead: f32,
+    /// Distance at which the target is considered reached,
+    /// checked against the path travelled in the last step.
+    pub reach_distance: f32,
+    /// Write state changes to [`ProjectileEventBuffer`], off by default
+    /// so existing [`EventProjectileSystem`](crate::EventProjectileSystem)s are not triggered.
+    pub emit_events: bool,
+    /// Time since launch.
+    pub age: f32,
+    /// Position of the target, written by the plugin.
+    pub target_position: Option<Vec3>,
+    /// Velocity of the target, written by the plugin.
+    pub target_velocity: Vec3,
+    /// Current state.
+    pub state: HomingState,
+}
+
+impl Default for Homing {
+    fn default() -> Self {
+        Self {
+            target: None,
+            position: Vec3::ZERO,
+            previous_position: Vec3::ZERO,
+            velocity: Vec3::NEG_Z,
+            turn_rate: 4.,
+            speed: |_| 10.,
+            lead: 1.,
+            reach_distance: 0.5,
+            emit_events: false,
+            age: 0.,
+            target_position: None,
+            target_velocity: Vec3::ZERO,
+            state: HomingState::Tracking,
+        }
+    }
+}
+
+impl Homing {
+    /// Create a homing projectile launched at a position and velocity.
+    pub fn new(target: Entity, position: Vec3, velocity: Vec3) -> Self {
+        Self {
+            target: Some(target),
+            position,
+            previous_position: position,
+            velocity,
+            ..Default::default()
+        }
+    }
+
+    /// Steer towards the target and advance position.
+    pub fn update(&mut self, dt: f32) {
+        self.age += dt;
+        let speed = (self.speed)(self.age);
+        let mut dir = self.velocity.normalize_or(Vec3::NEG_Z);
+        if let (HomingState::Tracking, Some(target)) = (self.state, self.target_position) {
+            let time = self.position.distance(target) / speed.max(f32::EPSILON);
+            let aim = target + self.target_velocity * time * self.lead;
+            if let Some(desired) = (aim - self.position).try_normalize() {
+                let angle = dir.angle_between(desired);
+                let max = self.turn_rate * dt;
+                dir = if angle <= max {
+                    desired
+                } else {
+                    Quat::IDENTITY.slerp(Quat::from_rotation_arc(dir, desired), max / angle) * dir
+                };
+            }
+        }
+        self.velocity = dir * speed;
+        self.previous_position = self.position;
+        self.position += self.velocity * dt;
+    }
+
+    /// Obtain a transform facing the direction of travel.
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.position).looking_to(self.velocity, Vec3::Y)
+    }
+}
+
+/// Tracks the velocity of an entity targeted by [`Homing`] projectiles for lead prediction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Component)]
+pub struct HomingTarget {
+    /// Velocity estimated from [`GlobalTransform`].
+    pub velocity: Vec3,
+    previous: Option<Vec3>,
+}
+
+pub(crate) fn track_homing_targets(
+    time: Res<Time>,
+    mut query: Query<(&GlobalTransform, &mut HomingTarget)>,
+) {
+    let dt = time.delta_secs();
+    for (transform, mut target) in &mut query {
+        let position = transform.translation();
+        if let Some(previous) = target.previous {
+            if dt > 0. {
+                target.velocity = (position - previous) / dt;
+            }
+        }
+        target.previous = Some(position);
+    }
+}
+
+/// Closest distance to the origin on the segment from `a` to `b`.
+fn segment_distance(a: Vec3, b: Vec3) -> f32 {
+    let ab = b - a;
+    let len = ab.length_squared();
+    if len <= 0. {
+        return a.length();
+    }
+    (a + ab * (-a.dot(ab) / len).clamp(0., 1.)).length()
+}
+
+/// Write target positions to [`Homing`] projectiles and update their states.
+pub(crate) fn update_homing<P: Projectile>(
+    buffer: &mut ProjectileBuffer,
+    targets: &dyn Fn(Entity) -> Option<(Vec3, Vec3)>,
+    to_world: Affine3A,
+    mut events: Option<&mut ProjectileEventBuffer>,
+) {
+    let to_local = to_world.inverse();
+    for item in buffer.get_mut::<P>() {
+        if item.is_expired() {
+            continue;
+        }
+        let Some(homing) = item.as_homing_mut() else {
+            continue;
+        };
+        let (HomingState::Tracking, Some(target)) = (homing.state, homing.target) else {
+            continue;
+        };
+        let event = match targets(target) {
+            None => {
+                homing.state = HomingState::Lost;
+                homing.target_position = None;
+                ProjectileEventType::TargetLost
+            }
+            Some((position, velocity)) => {
+                let position = to_local.transform_point3(position);
+                let previous = homing.target_position.unwrap_or(position);
+                homing.target_position = Some(position);
+                homing.target_velocity = to_local.transform_vector3(velocity);
+                // Relative motion in the last step, so fast projectiles cannot tunnel past.
+                let distance = segment_distance(
+                    homing.previous_position - previous,
+                    homing.position - position,
+                );
+                if distance > homing.reach_distance {
+                    continue;
+                }
+                homing.state = HomingState::Reached;
+                ProjectileEventType::TargetReached
+            }
+        };
+        let Some(events) = events.as_deref_mut().filter(|_| homing.emit_events) else {
+            continue;
+        };
+        events.push(ProjectileEvent {
+            event,
+            seed: item.get_seed(),
+            index: item.get_index(),
+            lifetime: item.get_lifetime(),
+            position: item.get_position(),
+            tangent: item.get_tangent(),
+            normal: Vec3::ZERO,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        math::{Affine3A, Vec3},
+        prelude::Entity,
+        transform::components::Transform,
+    };
+
+    use super::{update_homing, Homing, HomingState};
+    use crate::{
+        ExpirationState, Projectile, ProjectileBuffer, ProjectileEventBuffer, ProjectileEventType,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    struct Missile(Homing);
+
+    impl Projectile for Missile {
+        fn get_transform(&self) -> Transform {
+            self.0.transform()
+        }
+
+        fn update(&mut self, dt: f32) {
+            self.0.update(dt);
+        }
+
+        fn expiration_state(&self) -> ExpirationState {
+            ExpirationState::None
+        }
+
+        fn as_homing_mut(&mut self) -> Option<&mut Homing> {
+            Some(&mut self.0)
+        }
+    }
+
+    fn target() -> Entity {
+        Entity::from_raw(7)
+    }
+
+    fn step(
+        buffer: &mut ProjectileBuffer,
+        target: Option<Vec3>,
+        events: &mut ProjectileEventBuffer,
+    ) {
+        update_homing::<Missile>(
+            buffer,
+            &|entity| {
+                target
+                    .filter(|_| entity == self::target())
+                    .map(|x| (x, Vec3::ZERO))
+            },
+            Affine3A::IDENTITY,
+            Some(events),
+        );
+    }
+
+    fn missile(emit_events: bool) -> Missile {
+        Missile(Homing {
+            turn_rate: 0.,
+            speed: |_| 100.,
+            emit_events,
+            ..Homing::new(target(), Vec3::ZERO, Vec3::X)
+        })
+    }
+
+    #[test]
+    fn fast_missile_reaches() {
+        let mut buffer = ProjectileBuffer::new_retain::<Missile>(4);
+        buffer.extend([missile(true)]);
+        let mut events = ProjectileEventBuffer::default();
+        let target = Vec3::new(5., 0.2, 0.);
+        step(&mut buffer, Some(target), &mut events);
+        // Passes the target in a single step, from x = 0 to x = 10.
+        buffer.get_mut::<Missile>()[0].update(0.1);
+        assert!(buffer.get::<Missile>()[0].0.position.distance(target) > 0.5);
+        step(&mut buffer, Some(target), &mut events);
+        assert_eq!(buffer.get::<Missile>()[0].0.state, HomingState::Reached);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, ProjectileEventType::TargetReached);
+    }
+
+    #[test]
+    fn fast_missile_misses() {
+        let mut buffer = ProjectileBuffer::new_retain::<Missile>(4);
+        buffer.extend([missile(true)]);
+        let mut events = ProjectileEventBuffer::default();
+        let target = Some(Vec3::new(5., 2., 0.));
+        step(&mut buffer, target, &mut events);
+        buffer.get_mut::<Missile>()[0].update(0.1);
+        step(&mut buffer, target, &mut events);
+        assert_eq!(buffer.get::<Missile>()[0].0.state, HomingState::Tracking);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn events_opt_in() {
+        let mut buffer = ProjectileBuffer::new_retain::<Missile>(4);
+        buffer.extend([missile(false), missile(true)]);
+        let mut events = ProjectileEventBuffer::default();
+        step(&mut buffer, None, &mut events);
+        let particles = buffer.get::<Missile>();
+        assert_eq!(particles[0].0.state, HomingState::Lost);
+        assert_eq!(particles[1].0.state, HomingState::Lost);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, ProjectileEventType::TargetLost);
+    }
+}
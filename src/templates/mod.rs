@@ -1,2 +1,7 @@
 mod trails;
 pub use trails::*;
+mod flocking;
+pub use flocking::*;
+mod homing;
+pub(crate) use homing::{track_homing_targets, update_homing};
+pub use homing::{Homing, HomingState, HomingTarget};
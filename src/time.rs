@@ -0,0 +1,272 @@
+use std::{any::TypeId, marker::PhantomData};
+
+use bevy::{
+    app::{App, Plugin, PreUpdate},
+    ecs::system::SystemParam,
+    prelude::{Component, Res, ResMut, Resource},
+    time::{Real, Time, Virtual},
+    transform::components::Transform,
+    utils::HashMap,
+};
+
+use crate::ProjectileBuffer;
+
+/// Determines how [`projectile_simulation_system`](crate::projectile_simulation_system)
+/// advances time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Resource)]
+pub enum ProjectileTimestep {
+    /// Advance by the frame's delta time once per frame.
+    #[default]
+    Variable,
+    /// Advance in steps of `1 / hertz` seconds, at most `max_substeps` times per frame.
+    ///
+    /// Rendering interpolates between transforms of the last two steps,
+    /// time exceeding `max_substeps` is discarded.
+    Fixed { hertz: f32, max_substeps: u32 },
+}
+
+impl ProjectileTimestep {
+    /// Create a fixed timestep with at most `8` substeps per frame.
+    ///
+    /// # Panics
+    ///
+    /// If `hertz` is not positive and finite.
+    pub const fn fixed(hertz: f32) -> Self {
+        assert!(
+            hertz > 0. && hertz.is_finite(),
+            "Fixed timestep must be positive and finite."
+        );
+        ProjectileTimestep::Fixed {
+            hertz,
+            max_substeps: 8,
+        }
+    }
+
+    /// Returns `true` if in fixed timestep mode.
+    pub const fn is_fixed(&self) -> bool {
+        matches!(self, ProjectileTimestep::Fixed { .. })
+    }
+
+    /// Advance a buffer's clock by `dt`,
+    /// returns the number of steps to simulate and the length of each step.
+    pub(crate) fn advance(&self, buffer: &mut ProjectileBuffer, dt: f32) -> (usize, f32) {
+        match *self {
+            ProjectileTimestep::Variable => {
+                buffer.accumulated = 0.;
+                buffer.interpolation = None;
+                buffer.delta = dt;
+                (1, dt)
+            }
+            ProjectileTimestep::Fixed {
+                hertz,
+                max_substeps,
+            } => {
+                // Guard against `Fixed` constructed without `fixed`.
+                if !(hertz > 0. && hertz.is_finite()) {
+                    buffer.interpolation = None;
+                    buffer.delta = 0.;
+                    return (0, 0.);
+                }
+                let step = 1. / hertz;
+                buffer.accumulated += dt;
+                let steps = ((buffer.accumulated / step) as usize).min(max_substeps as usize);
+                buffer.accumulated = (buffer.accumulated - steps as f32 * step).min(step);
+                buffer.interpolation = Some(buffer.accumulated / step);
+                buffer.delta = steps as f32 * step;
+                (steps, step)
+            }
+        }
+    }
+}
+
+/// Blend between two transforms.
+pub(crate) fn interpolate_transform(from: &Transform, to: &Transform, fac: f32) -> Transform {
+    Transform {
+        translation: from.translation.lerp(to.translation, fac),
+        rotation: from.rotation.slerp(to.rotation, fac),
+        scale: from.scale.lerp(to.scale, fac),
+    }
+}
+
+/// Clock a [`ProjectileCluster`](crate::ProjectileCluster) reads delta time from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProjectileClockSource {
+    /// [`Time<Virtual>`], stops when the game is paused.
+    #[default]
+    Virtual,
+    /// [`Time<Real>`], keeps running when the game is paused.
+    Real,
+    /// A [`Time<T>`] registered via [`ProjectileClockPlugin`].
+    Custom(TypeId),
+}
+
+impl ProjectileClockSource {
+    /// Read from a [`Time<T>`] registered via [`ProjectileClockPlugin`].
+    pub fn custom<T: 'static>() -> Self {
+        ProjectileClockSource::Custom(TypeId::of::<T>())
+    }
+}
+
+/// Per cluster time control, place next to a [`ProjectileCluster`](crate::ProjectileCluster).
+///
+/// If not present, the cluster runs on [`Time<Virtual>`] with no scaling.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct ProjectileClock {
+    /// Multiplier applied to delta time.
+    pub time_scale: f32,
+    /// If true, the cluster is not simulated and does not spawn from its parent.
+    pub paused: bool,
+    /// Clock to read delta time from.
+    pub source: ProjectileClockSource,
+}
+
+impl Default for ProjectileClock {
+    fn default() -> Self {
+        Self {
+            time_scale: 1.,
+            paused: false,
+            source: ProjectileClockSource::Virtual,
+        }
+    }
+}
+
+impl ProjectileClock {
+    /// Create a clock that reads from [`Time<Real>`].
+    pub fn real() -> Self {
+        Self {
+            source: ProjectileClockSource::Real,
+            ..Default::default()
+        }
+    }
+
+    /// Create a clock that reads from a [`Time<T>`] registered via [`ProjectileClockPlugin`].
+    pub fn custom<T: 'static>() -> Self {
+        Self {
+            source: ProjectileClockSource::custom::<T>(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the time scale.
+    pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    /// Set the paused flag.
+    pub fn with_paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+}
+
+/// Delta times of user defined clocks, written by [`ProjectileClockPlugin`].
+#[derive(Debug, Default, Resource)]
+pub struct CustomProjectileClocks(HashMap<TypeId, f32>);
+
+/// Allow [`ProjectileClock`]s to read from a user defined [`Time<T>`].
+///
+/// Delta time is read in [`PreUpdate`], `Time<T>` should be advanced before that.
+pub struct ProjectileClockPlugin<T>(PhantomData<T>);
+
+impl<T> Default for ProjectileClockPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Default + Send + Sync + 'static> Plugin for ProjectileClockPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CustomProjectileClocks>();
+        app.add_systems(PreUpdate, sync_custom_clock::<T>);
+    }
+}
+
+fn sync_custom_clock<T: Default + Send + Sync + 'static>(
+    time: Option<Res<Time<T>>>,
+    mut clocks: ResMut<CustomProjectileClocks>,
+) {
+    if let Some(time) = time {
+        clocks.0.insert(TypeId::of::<T>(), time.delta_secs());
+    }
+}
+
+/// [`SystemParam`] for reading delta times of [`ProjectileClock`]s.
+///
+/// Delta times are relative to the schedule's [`Time`], so in `FixedUpdate`
+/// each run advances by the fixed timestep instead of the frame's delta time.
+#[derive(SystemParam)]
+pub struct ProjectileClocks<'w> {
+    time: Res<'w, Time>,
+    virtual_time: Res<'w, Time<Virtual>>,
+    real_time: Res<'w, Time<Real>>,
+    custom: Option<Res<'w, CustomProjectileClocks>>,
+}
+
+impl ProjectileClocks<'_> {
+    /// Obtain the portion of the frame covered by this run of the schedule,
+    /// `1.0` outside of fixed schedules.
+    fn frame_fraction(&self) -> f32 {
+        let frame = self.virtual_time.delta_secs();
+        if frame > 0. {
+            self.time.delta_secs() / frame
+        } else {
+            1.
+        }
+    }
+
+    /// Obtain the scaled delta time of a clock, returns `None` if paused.
+    pub fn delta(&self, clock: Option<&ProjectileClock>) -> Option<f32> {
+        let Some(clock) = clock else {
+            return Some(self.time.delta_secs());
+        };
+        if clock.paused {
+            return None;
+        }
+        let dt = match clock.source {
+            ProjectileClockSource::Virtual => self.time.delta_secs(),
+            ProjectileClockSource::Real => self.real_time.delta_secs() * self.frame_fraction(),
+            ProjectileClockSource::Custom(id) => {
+                self.custom
+                    .as_ref()
+                    .and_then(|x| x.0.get(&id).copied())
+                    .unwrap_or(0.)
+                    * self.frame_fraction()
+            }
+        };
+        Some(dt * clock.time_scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ProjectileBuffer;
+
+    use super::ProjectileTimestep;
+
+    #[test]
+    fn fixed_steps() {
+        let mut buffer = ProjectileBuffer::default();
+        let timestep = ProjectileTimestep::fixed(10.);
+        assert_eq!(timestep.advance(&mut buffer, 0.25), (2, 0.1));
+        assert!((buffer.interpolation.unwrap() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_rejects_zero() {
+        ProjectileTimestep::fixed(0.);
+    }
+
+    #[test]
+    fn invalid_hertz_does_not_step() {
+        let mut buffer = ProjectileBuffer::default();
+        for hertz in [0., -1., f32::NAN, f32::INFINITY] {
+            let timestep = ProjectileTimestep::Fixed {
+                hertz,
+                max_substeps: 8,
+            };
+            assert_eq!(timestep.advance(&mut buffer, 0.25), (0, 0.));
+        }
+    }
+}
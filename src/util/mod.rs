@@ -10,18 +10,23 @@ use bevy::{
     transform::components::Transform,
 };
 
+use crate::ParticleSeed;
+
+mod shape;
+pub use shape::{EmitMode, EmitShape};
+
 /// Create a [`fastrand::Rng`] from a seed.
-pub fn into_rng(seed: f32) -> fastrand::Rng {
-    fastrand::Rng::with_seed((seed as f64 * u64::MAX as f64) as u64)
+pub fn into_rng(seed: ParticleSeed) -> fastrand::Rng {
+    seed.rng()
 }
 
 /// Create a random 2d unit vector.
-pub fn random_circle(seed: f32) -> Vec2 {
-    Vec2::from_angle(seed * (2. * PI))
+pub fn random_circle(seed: ParticleSeed) -> Vec2 {
+    Vec2::from_angle(into_rng(seed).f32() * (2. * PI))
 }
 
 /// Create a random 2d vector inside a `r=1` circle.
-pub fn random_solid_circle(seed: f32) -> Vec2 {
+pub fn random_solid_circle(seed: ParticleSeed) -> Vec2 {
     let mut rng = into_rng(seed);
     let r = rng.f32().sqrt();
     let (s, c) = (rng.f32() * 2. * PI).sin_cos();
@@ -33,7 +38,7 @@ fn lerp(a: f32, b: f32, fac: f32) -> f32 {
 }
 
 /// Create a random 3d unit vector near a direction.
-pub fn random_cone(points_to: Vec3, angle: f32, seed: f32) -> Vec3 {
+pub fn random_cone(points_to: Vec3, angle: f32, seed: ParticleSeed) -> Vec3 {
     let mut rng = into_rng(seed);
     let theta = rng.f32() * 2. * PI;
     let angle = angle.cos();
@@ -44,9 +49,9 @@ pub fn random_cone(points_to: Vec3, angle: f32, seed: f32) -> Vec3 {
 }
 
 /// Create a random 3d unit vector.
-pub fn random_sphere(seed: f32) -> Vec3 {
+pub fn random_sphere(seed: ParticleSeed) -> Vec3 {
     let mut rng = into_rng(seed);
-    let theta = seed * 2. * PI;
+    let theta = rng.f32() * 2. * PI;
     let phi = (rng.f32() * 2. - 1.).acos();
     let (ps, pc) = phi.sin_cos();
     let (ts, tc) = theta.sin_cos();
@@ -62,7 +67,7 @@ pub fn transform_from_derivative(mut f: impl FnMut(f32) -> Vec3, lifetime: f32)
 }
 
 /// Create a random [`Quat`].
-pub fn random_quat(seed: f32) -> Quat {
+pub fn random_quat(seed: ParticleSeed) -> Quat {
     let mut rng = into_rng(seed);
     let u1 = rng.f32();
     let u2 = rng.f32();
@@ -0,0 +1,458 @@
+use std::f32::consts::TAU;
+
+use bevy::math::Vec3;
+
+use super::{lerp, random_cone, random_sphere};
+use crate::ParticleSeed;
+
+/// How an [`EmitShape`] picks where along the shape a particle spawns.
+///
+/// Sequential modes step along the shape's main parameter,
+/// i.e. the angle of circular shapes or the length of lines, by particle index,
+/// while other parameters remain random.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmitMode {
+    /// Spawn at random positions.
+    #[default]
+    Random,
+    /// Spawn at `count` evenly spaced positions in order, then restart from the beginning.
+    ///
+    /// On closed shapes, i.e. a full circle, this loops around the edge.
+    Loop { count: u32 },
+    /// Spawn at `count + 1` evenly spaced positions in order, then walk back.
+    PingPong { count: u32 },
+}
+
+/// A seed driven emitter shape, obtains a position and a direction for a particle.
+///
+/// Circular shapes lie on the `XZ` plane, and their `arc` starts at `+X`, use `TAU` for a full circle.
+/// `thickness` ranges from `0.0` for emitting from the surface to `1.0` for emitting from the whole volume.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmitShape {
+    /// Spawn at the origin in random directions.
+    Point,
+    /// Spawn inside a box, moving up. Steps along `X`.
+    Box { half_size: Vec3 },
+    /// Spawn on the surface of a box, moving outwards. Steps along the faces.
+    BoxSurface { half_size: Vec3 },
+    /// Spawn inside a spherical shell, moving outwards. Steps around `Y`.
+    Sphere { radius: f32, thickness: f32 },
+    /// Spawn inside the `+Y` half of a spherical shell, moving outwards. Steps around `Y`.
+    Hemisphere { radius: f32, thickness: f32 },
+    /// Spawn inside a disk, or an annulus if `inner_radius` is not `0`, moving up. Steps along the arc.
+    Disk {
+        radius: f32,
+        inner_radius: f32,
+        arc: f32,
+    },
+    /// Spawn inside a torus around `Y`, moving away from the center of the tube. Steps along the arc.
+    Torus {
+        radius: f32,
+        tube_radius: f32,
+        thickness: f32,
+        arc: f32,
+    },
+    /// Spawn inside a cylinder along `Y`, moving away from the axis. Steps along the arc.
+    Cylinder {
+        radius: f32,
+        half_height: f32,
+        thickness: f32,
+        arc: f32,
+    },
+    /// Spawn inside a capsule along `Y`, moving away from the axis. Steps around `Y`.
+    Capsule {
+        radius: f32,
+        half_height: f32,
+        thickness: f32,
+    },
+    /// Spawn on a line segment, moving in a random direction perpendicular to it. Steps along the line.
+    Line { start: Vec3, end: Vec3 },
+    /// Spawn on connected line segments, moving in a random direction perpendicular to them.
+    /// Steps along the lines.
+    Polyline { points: Vec<Vec3>, closed: bool },
+    /// Spawn on a circular arc, moving outwards within `spread` radians. Steps along the arc.
+    Arc { radius: f32, arc: f32, spread: f32 },
+}
+
+/// Scale a unit radius so points are uniformly distributed in a shell of `dimension`.
+fn shell(thickness: f32, dimension: i32, fac: f32) -> f32 {
+    let inner = (1. - thickness).clamp(0., 1.);
+    let inv = 1. / dimension as f32;
+    lerp(inner.powi(dimension), 1., fac).powf(inv)
+}
+
+/// A random unit vector perpendicular to `axis`.
+fn perpendicular(axis: Vec3, fac: f32) -> Vec3 {
+    let (a, b) = axis.normalize_or(Vec3::Y).any_orthonormal_pair();
+    let (s, c) = (fac * TAU).sin_cos();
+    a * c + b * s
+}
+
+/// A unit vector on the `XZ` plane.
+fn radial(angle: f32) -> Vec3 {
+    let (s, c) = angle.sin_cos();
+    Vec3::new(c, 0., s)
+}
+
+/// A unit vector with azimuth `angle` and height `y`.
+fn spherical(angle: f32, y: f32) -> Vec3 {
+    radial(angle) * (1. - y * y).max(0.).sqrt() + Vec3::new(0., y, 0.)
+}
+
+impl EmitMode {
+    /// Obtain the main parameter in `0.0..=1.0`.
+    fn fac(self, index: u32, closed: bool, rng: &mut fastrand::Rng) -> f32 {
+        match self {
+            EmitMode::Random => rng.f32(),
+            EmitMode::Loop { count } => {
+                let count = count.max(1);
+                let steps = if closed { count } else { count - 1 };
+                (index % count) as f32 / steps.max(1) as f32
+            }
+            EmitMode::PingPong { count } => {
+                // In `u64` since `count * 2` overflows `u32`.
+                let count = count.max(1) as u64;
+                let step = index as u64 % (count * 2);
+                step.min(count * 2 - step) as f32 / count as f32
+            }
+        }
+    }
+}
+
+impl EmitShape {
+    /// Returns `true` if the main parameter wraps around, i.e. full circles.
+    fn is_closed(&self) -> bool {
+        match self {
+            EmitShape::Point
+            | EmitShape::BoxSurface { .. }
+            | EmitShape::Sphere { .. }
+            | EmitShape::Hemisphere { .. }
+            | EmitShape::Capsule { .. } => true,
+            EmitShape::Box { .. } | EmitShape::Line { .. } => false,
+            EmitShape::Polyline { closed, .. } => *closed,
+            EmitShape::Disk { arc, .. }
+            | EmitShape::Torus { arc, .. }
+            | EmitShape::Cylinder { arc, .. }
+            | EmitShape::Arc { arc, .. } => arc.abs() >= TAU,
+        }
+    }
+
+    /// Obtain a random position and direction from a seed.
+    pub fn sample(&self, seed: ParticleSeed) -> (Vec3, Vec3) {
+        self.sample_with(EmitMode::Random, 0, seed)
+    }
+
+    /// Obtain a position and direction for the `index`-th particle spawned.
+    pub fn sample_with(&self, mode: EmitMode, index: u32, seed: ParticleSeed) -> (Vec3, Vec3) {
+        let mut rng = seed.rng();
+        let t = mode.fac(index, self.is_closed(), &mut rng);
+        match self {
+            EmitShape::Point => (Vec3::ZERO, random_sphere(seed.derive(1))),
+            EmitShape::Box { half_size } => {
+                let p = Vec3::new(t, rng.f32(), rng.f32()) * 2. - 1.;
+                (p * *half_size, Vec3::Y)
+            }
+            EmitShape::BoxSurface { half_size } => {
+                let h = *half_size;
+                let areas = [h.y * h.z, h.x * h.z, h.x * h.y];
+                let total: f32 = areas.iter().sum::<f32>() * 2.;
+                if total <= 0. {
+                    return (Vec3::ZERO, Vec3::Y);
+                }
+                let mut s = t * total;
+                let mut face = 0;
+                while face < 5 && s >= areas[face / 2] {
+                    s -= areas[face / 2];
+                    face += 1;
+                }
+                let axis = face / 2;
+                let u = (s / areas[axis]).clamp(0., 1.) * 2. - 1.;
+                let v = rng.f32() * 2. - 1.;
+                let sign = if face % 2 == 0 { 1. } else { -1. };
+                let mut normal = Vec3::ZERO;
+                normal[axis] = sign;
+                let mut p = normal;
+                p[(axis + 1) % 3] = u;
+                p[(axis + 2) % 3] = v;
+                (p * h, normal)
+            }
+            EmitShape::Sphere { radius, thickness } => {
+                let dir = spherical(t * TAU, rng.f32() * 2. - 1.);
+                (dir * shell(*thickness, 3, rng.f32()) * *radius, dir)
+            }
+            EmitShape::Hemisphere { radius, thickness } => {
+                let dir = spherical(t * TAU, rng.f32());
+                (dir * shell(*thickness, 3, rng.f32()) * *radius, dir)
+            }
+            EmitShape::Disk {
+                radius,
+                inner_radius,
+                arc,
+            } => {
+                let r = lerp(inner_radius * inner_radius, radius * radius, rng.f32()).sqrt();
+                (radial(t * arc) * r, Vec3::Y)
+            }
+            EmitShape::Torus {
+                radius,
+                tube_radius,
+                thickness,
+                arc,
+            } => {
+                let out = radial(t * arc);
+                let (s, c) = (rng.f32() * TAU).sin_cos();
+                let dir = out * c + Vec3::Y * s;
+                let r = shell(*thickness, 2, rng.f32()) * tube_radius;
+                (out * *radius + dir * r, dir)
+            }
+            EmitShape::Cylinder {
+                radius,
+                half_height,
+                thickness,
+                arc,
+            } => {
+                let dir = radial(t * arc);
+                let r = shell(*thickness, 2, rng.f32()) * radius;
+                let y = (rng.f32() * 2. - 1.) * half_height;
+                (dir * r + Vec3::new(0., y, 0.), dir)
+            }
+            EmitShape::Capsule {
+                radius,
+                half_height,
+                thickness,
+            } => {
+                // Ratio of side area to cap area is `half_height : radius`.
+                if rng.f32() * (half_height + radius) < *half_height {
+                    let dir = radial(t * TAU);
+                    let r = shell(*thickness, 2, rng.f32()) * radius;
+                    let y = (rng.f32() * 2. - 1.) * half_height;
+                    (dir * r + Vec3::new(0., y, 0.), dir)
+                } else {
+                    let dir = spherical(t * TAU, rng.f32() * 2. - 1.);
+                    let r = shell(*thickness, 3, rng.f32()) * radius;
+                    (
+                        dir * r + Vec3::new(0., half_height.copysign(dir.y), 0.),
+                        dir,
+                    )
+                }
+            }
+            EmitShape::Line { start, end } => {
+                (start.lerp(*end, t), perpendicular(*end - *start, rng.f32()))
+            }
+            EmitShape::Polyline { points, closed } => {
+                let segments = || {
+                    let closing = points.last().zip(points.first()).filter(|_| *closed);
+                    points.iter().zip(points.iter().skip(1)).chain(closing)
+                };
+                let total: f32 = segments().map(|(a, b)| a.distance(*b)).sum();
+                let mut s = t * total;
+                for (a, b) in segments() {
+                    let len = a.distance(*b);
+                    if s <= len {
+                        let fac = if len > 0. { s / len } else { 0. };
+                        return (a.lerp(*b, fac), perpendicular(*b - *a, rng.f32()));
+                    }
+                    s -= len;
+                }
+                match points.last() {
+                    Some(p) => (*p, random_sphere(seed.derive(1))),
+                    None => (Vec3::ZERO, Vec3::Y),
+                }
+            }
+            EmitShape::Arc {
+                radius,
+                arc,
+                spread,
+            } => {
+                let out = radial(t * arc);
+                (out * *radius, random_cone(out, *spread, seed.derive(1)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{PI, TAU};
+
+    use bevy::math::Vec3;
+
+    use super::{EmitMode, EmitShape};
+    use crate::ParticleSeed;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn positions(shape: &EmitShape, mode: EmitMode, count: u32) -> Vec<Vec3> {
+        (0..count)
+            .map(|i| shape.sample_with(mode, i, ParticleSeed(i as u64)).0)
+            .collect()
+    }
+
+    fn assert_near(a: &[Vec3], b: &[Vec3]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert!(a.distance(*b) < EPSILON, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn loop_positions() {
+        let line = EmitShape::Line {
+            start: Vec3::ZERO,
+            end: Vec3::X * 3.,
+        };
+        let expected = [0., 1., 2., 3., 0., 1.].map(|x| Vec3::X * x);
+        assert_near(&positions(&line, EmitMode::Loop { count: 4 }, 6), &expected);
+
+        let circle = EmitShape::Arc {
+            radius: 1.,
+            arc: TAU,
+            spread: 0.,
+        };
+        let expected = [Vec3::X, Vec3::Z, -Vec3::X, -Vec3::Z, Vec3::X];
+        assert_near(
+            &positions(&circle, EmitMode::Loop { count: 4 }, 5),
+            &expected,
+        );
+    }
+
+    #[test]
+    fn ping_pong_positions() {
+        let line = EmitShape::Line {
+            start: Vec3::ZERO,
+            end: Vec3::X * 2.,
+        };
+        let expected = [0., 1., 2., 1., 0., 1.].map(|x| Vec3::X * x);
+        assert_near(
+            &positions(&line, EmitMode::PingPong { count: 2 }, 6),
+            &expected,
+        );
+
+        let arc = EmitShape::Arc {
+            radius: 1.,
+            arc: PI,
+            spread: 0.,
+        };
+        let expected = [Vec3::X, Vec3::Z, -Vec3::X, Vec3::Z, Vec3::X];
+        assert_near(
+            &positions(&arc, EmitMode::PingPong { count: 2 }, 5),
+            &expected,
+        );
+    }
+
+    #[test]
+    fn ping_pong_large_count() {
+        let mut rng = fastrand::Rng::with_seed(0);
+        let mode = EmitMode::PingPong { count: u32::MAX };
+        assert_eq!(mode.fac(0, false, &mut rng), 0.);
+        assert_eq!(mode.fac(u32::MAX, false, &mut rng), 1.);
+        // Second half of the cycle heads back down.
+        let count = u32::MAX / 2 + 1;
+        let mode = EmitMode::PingPong { count };
+        assert_eq!(mode.fac(count, false, &mut rng), 1.);
+        assert_eq!(mode.fac(u32::MAX, false, &mut rng), 1. / count as f32);
+    }
+
+    #[test]
+    fn shapes_within_bounds() {
+        let (radius, height, thickness) = (2., 3., 0.25);
+        let within_shell =
+            |d: f32| (radius * (1. - thickness) - EPSILON..=radius + EPSILON).contains(&d);
+        let shapes: [(EmitShape, &dyn Fn(Vec3) -> bool); 11] = [
+            (EmitShape::Point, &|p| p == Vec3::ZERO),
+            (
+                EmitShape::Box {
+                    half_size: Vec3::new(1., 2., 3.),
+                },
+                &|p| p.abs().cmple(Vec3::new(1., 2., 3.) + EPSILON).all(),
+            ),
+            (
+                EmitShape::BoxSurface {
+                    half_size: Vec3::new(1., 2., 3.),
+                },
+                &|p| {
+                    let d = Vec3::new(1., 2., 3.) - p.abs();
+                    d.cmpge(Vec3::splat(-EPSILON)).all() && d.min_element() < EPSILON
+                },
+            ),
+            (EmitShape::Sphere { radius, thickness }, &|p| {
+                within_shell(p.length())
+            }),
+            (EmitShape::Hemisphere { radius, thickness }, &|p| {
+                within_shell(p.length()) && p.y >= -EPSILON
+            }),
+            (
+                EmitShape::Disk {
+                    radius,
+                    inner_radius: 1.,
+                    arc: PI,
+                },
+                &|p| {
+                    (1. - EPSILON..=radius + EPSILON).contains(&p.length())
+                        && p.y == 0.
+                        && p.z >= -EPSILON
+                },
+            ),
+            (
+                EmitShape::Torus {
+                    radius: 5.,
+                    tube_radius: radius,
+                    thickness,
+                    arc: TAU,
+                },
+                &|p| {
+                    let ring = Vec3::new(p.x, 0., p.z).normalize() * 5.;
+                    within_shell(p.distance(ring))
+                },
+            ),
+            (
+                EmitShape::Cylinder {
+                    radius,
+                    half_height: height,
+                    thickness,
+                    arc: TAU,
+                },
+                &|p| within_shell(p.with_y(0.).length()) && p.y.abs() <= height + EPSILON,
+            ),
+            (
+                EmitShape::Capsule {
+                    radius,
+                    half_height: height,
+                    thickness,
+                },
+                &|p| {
+                    let axis = Vec3::new(0., p.y.clamp(-height, height), 0.);
+                    within_shell(p.distance(axis))
+                },
+            ),
+            (
+                EmitShape::Polyline {
+                    points: vec![Vec3::ZERO, Vec3::X, Vec3::new(1., 1., 0.)],
+                    closed: false,
+                },
+                &|p| {
+                    (p.y.abs() < EPSILON && (-EPSILON..=1. + EPSILON).contains(&p.x))
+                        || ((p.x - 1.).abs() < EPSILON && (-EPSILON..=1. + EPSILON).contains(&p.y))
+                },
+            ),
+            (
+                EmitShape::Arc {
+                    radius,
+                    arc: TAU,
+                    spread: 0.5,
+                },
+                &|p| (p.length() - radius).abs() < EPSILON && p.y == 0.,
+            ),
+        ];
+        for (shape, within) in &shapes {
+            for mode in [
+                EmitMode::Random,
+                EmitMode::Loop { count: 7 },
+                EmitMode::PingPong { count: 7 },
+            ] {
+                for (i, p) in positions(shape, mode, 200).into_iter().enumerate() {
+                    assert!(within(p), "{shape:?} {mode:?} {i}: {p}");
+                }
+            }
+        }
+    }
+}